@@ -1,13 +1,13 @@
 use hex_color::HexColor;
 use ass_parser::AssFile;
-use ass_parser::{ScriptInfo, V4Format, Events, Dialogue};
+use ass_parser::{ScriptInfo, V4Format, Events, Dialogue, Timestamp};
 
 fn main() {
     let mut ass_file = AssFile::new();
 
     let dialogue = Dialogue::new()
-        .set_start("0:00:00:00")
-        .set_end("0:00:02:00")
+        .set_start(Timestamp::new(0, 0, 0, 0))
+        .set_end(Timestamp::new(0, 0, 2, 0))
         .set_text("Hello Friend!")
         .set_colour(HexColor::YELLOW);
 
@@ -1,14 +1,15 @@
-use ass_parser::{AssFile, Dialogue, AssFileOptions};
+use ass_parser::{AssFile, Dialogue, AssColor, ParseError};
 use hex_color::HexColor;
 
-fn main() -> Result<(), std::io::Error>{
+fn main() -> Result<(), ParseError>{
     let mut ass_file = AssFile::from_file("subtitles.ass")?;
     let dialogue = Dialogue::default()
         .set_text("Hello Friend!");
-    let primary_color = AssFileOptions::get_ass_color(HexColor::RED);
+    let primary_color = AssColor::from(HexColor::RED);
 
     ass_file.components.v4
-        .set_primarycolour(&primary_color);
+        .get_style_mut("Default")
+        .map(|style| style.set_primarycolour(primary_color));
         
     ass_file.components.events
         .add_dialogue(dialogue);
@@ -1,11 +1,11 @@
 use hex_color::HexColor;
-use ass_parser::{AssFile, AssFileOptions};
+use ass_parser::{AssFile, AssColor, ParseError};
 use ass_parser::{ScriptInfo, V4Format, Events, Dialogue};
 use rand;
 
-fn main() {
-    let hexcolor = AssFileOptions::get_ass_color(HexColor::YELLOW);
-    let srt_file = AssFile::from_srt("RapGod.srt");
+fn main() -> Result<(), ParseError> {
+    let hexcolor = AssColor::from(HexColor::YELLOW);
+    let srt_file = AssFile::from_srt("RapGod.srt")?;
     let mut ass_file = AssFile::new();
     let mut event = Events::default();
 
@@ -17,8 +17,8 @@ fn main() {
         let random_color:HexColor = rand::random();
 
         let dialogue = Dialogue::default()
-            .set_start(&start)
-            .set_end(&end)
+            .set_start(*start)
+            .set_end(*end)
             .set_text(&text)
             .set_colour(random_color);
 
@@ -33,9 +33,11 @@ fn main() {
 
     ass_file.components.v4
         .set_v4(V4Format::default())
-        .set_primarycolour(&hexcolor);
+        .set_primarycolour(hexcolor);
     ass_file.components.events
         .set_events(event);
 
     AssFile::save_file(&ass_file, "new_subtitle.ass");
+
+    Ok(())
 }
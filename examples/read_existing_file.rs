@@ -1,6 +1,6 @@
-use ass_parser::{AssFile, Dialogue};
+use ass_parser::{AssFile, Dialogue, ParseError};
 
-fn main() -> Result<(), std::io::Error>{
+fn main() -> Result<(), ParseError>{
     let ass_file = AssFile::from_file("examples/subtitles.ass")?;
     let dialogues: Vec<Dialogue> = ass_file.events.get_dialogues();
 
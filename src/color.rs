@@ -0,0 +1,134 @@
+use hex_color::HexColor;
+use std::fmt;
+use std::str::FromStr;
+
+/// A colour as used by `Advanced SubStation Alpha`.
+///
+/// Styles write colours as `&HAABBGGRR` and override tags as `&HBBGGRR&`, both in hexadecimal
+/// with the blue channel first. The alpha channel is a transparency: `00` is opaque and `FF` is
+/// fully transparent, which is the opposite of `HexColor`.
+///
+/// ```rust
+/// use ass_parser::AssColor;
+/// use hex_color::HexColor;
+///
+/// let blue = AssColor::from(HexColor::rgb(0, 0, 255));
+/// assert_eq!("&H00FF0000", blue.to_string());
+/// assert_eq!("&HFF0000&", blue.to_tag());
+///
+/// let color: AssColor = "&H80FF0000".parse().unwrap();
+/// assert_eq!(0x80, color.alpha);
+/// assert_eq!(HexColor::rgba(0, 0, 255, 0x7f), color.to_hex_color());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AssColor {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    /// transparency of the colour, `0` is opaque.
+    pub alpha: u8,
+}
+
+/// The error returned when a colour is not in any of the `.ass` colour formats.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidColor {
+    /// The text which could not be parsed.
+    pub text: String,
+}
+
+impl fmt::Display for InvalidColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid colour: {}", self.text)
+    }
+}
+
+impl std::error::Error for InvalidColor {}
+
+impl AssColor {
+    /// Create an opaque colour.
+    pub fn rgb(red: u8, green: u8, blue: u8) -> AssColor {
+        AssColor { red, green, blue, alpha: 0 }
+    }
+
+    /// Create a colour with a transparency, `0` is opaque and `255` fully transparent.
+    pub fn rgba(red: u8, green: u8, blue: u8, alpha: u8) -> AssColor {
+        AssColor { red, green, blue, alpha }
+    }
+
+    /// The `&HBBGGRR&` form used by the `\c`, `\1c`...`\4c` override tags. The alpha channel is
+    /// not part of it, see `to_alpha_tag`.
+    pub fn to_tag(&self) -> String {
+        format!("&H{:02X}{:02X}{:02X}&", self.blue, self.green, self.red)
+    }
+
+    /// The `&HAA&` form used by the `\alpha` and `\1a`...`\4a` override tags.
+    pub fn to_alpha_tag(&self) -> String {
+        format!("&H{:02X}&", self.alpha)
+    }
+
+    /// Convert to a `HexColor`, turning the transparency into an opacity.
+    pub fn to_hex_color(&self) -> HexColor {
+        HexColor::rgba(self.red, self.green, self.blue, 255 - self.alpha)
+    }
+
+    /// The decimal number used by the styles of SubStation Alpha v4 (`.ssa`) files.
+    pub fn to_ssa(&self) -> String {
+        (u32::from_le_bytes([self.red, self.green, self.blue, self.alpha]) as i32).to_string()
+    }
+
+    fn from_abgr(value: u32) -> AssColor {
+        let [red, green, blue, alpha] = value.to_le_bytes();
+        AssColor { red, green, blue, alpha }
+    }
+}
+
+impl FromStr for AssColor {
+    type Err = InvalidColor;
+
+    /// Parse `&HAABBGGRR`, `&HBBGGRR`, `&HBBGGRR&` or the decimal numbers of SSA v4 files.
+    /// Leading zeros may be left out, eg. `&H0` is black.
+    fn from_str(text: &str) -> Result<AssColor, InvalidColor> {
+        let invalid = || InvalidColor {
+            text: text.to_string(),
+        };
+        let trimmed = text.trim();
+        let hex = trimmed.strip_prefix("&H")
+            .or_else(|| trimmed.strip_prefix("&h"))
+            .or_else(|| trimmed.strip_prefix('H'))
+            .or_else(|| trimmed.strip_prefix('h'));
+
+        let value = match hex {
+            Some(hex) => {
+                let hex = hex.strip_suffix('&').unwrap_or(hex);
+                if hex.is_empty() || hex.len() > 8 {
+                    return Err(invalid());
+                }
+                u32::from_str_radix(hex, 16).map_err(|_| invalid())?
+            },
+            None => match trimmed.parse::<u32>() {
+                Ok(value) => value,
+                Err(_) => trimmed.parse::<i32>().map_err(|_| invalid())? as u32,
+            },
+        };
+        Ok(AssColor::from_abgr(value))
+    }
+}
+
+impl fmt::Display for AssColor {
+    /// The `&HAABBGGRR` form used by styles.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "&H{:02X}{:02X}{:02X}{:02X}", self.alpha, self.blue, self.green, self.red)
+    }
+}
+
+impl From<HexColor> for AssColor {
+    fn from(color: HexColor) -> AssColor {
+        AssColor::rgba(color.r, color.g, color.b, 255 - color.a)
+    }
+}
+
+impl From<AssColor> for HexColor {
+    fn from(color: AssColor) -> HexColor {
+        color.to_hex_color()
+    }
+}
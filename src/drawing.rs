@@ -0,0 +1,236 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A vector drawing, the text of a `Dialogue` after a `\p1` tag.
+///
+/// Drawings are made of commands followed by coordinates, eg. `m 0 0 l 100 0 100 100 0 100`
+/// draws a square. The coordinates can be moved, scaled and rotated before the drawing is
+/// written back.
+///
+/// ```rust
+/// use ass_parser::{Drawing, Point};
+///
+/// let mut square: Drawing = "m 0 0 l 10 0 10 10 0 10".parse().unwrap();
+/// square.scale(2.0, 2.0).translate(5.0, 0.0);
+///
+/// assert_eq!("m 5 0 l 25 0 25 20 5 20", square.to_string());
+/// assert_eq!(Some((Point::new(5.0, 0.0), Point::new(25.0, 20.0))), square.bounding_box());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Drawing {
+    pub commands: Vec<DrawingCommand>,
+}
+
+/// A point of a drawing. `y` grows towards the bottom of the screen.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A command of a drawing with its points.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawingCommand {
+    /// `m`, close the current shape and move to a point.
+    Move(Point),
+    /// `n`, move to a point without closing the current shape.
+    MoveNoClose(Point),
+    /// `l`, draw lines to each point.
+    Line(Vec<Point>),
+    /// `b`, draw cubic bezier curves, three points for each curve.
+    Bezier(Vec<Point>),
+    /// `s`, draw a cubic b-spline through at least three points.
+    Spline(Vec<Point>),
+    /// `p`, extend the b-spline to more points.
+    ExtendSpline(Vec<Point>),
+    /// `c`, close the b-spline.
+    CloseSpline,
+}
+
+/// The error returned when a drawing has an unknown command or a wrong number of coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidDrawing {
+    /// The text which could not be parsed.
+    pub text: String,
+}
+
+impl fmt::Display for InvalidDrawing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid drawing: {}", self.text)
+    }
+}
+
+impl std::error::Error for InvalidDrawing {}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+}
+
+impl DrawingCommand {
+    fn name(&self) -> char {
+        match self {
+            DrawingCommand::Move(_) => 'm',
+            DrawingCommand::MoveNoClose(_) => 'n',
+            DrawingCommand::Line(_) => 'l',
+            DrawingCommand::Bezier(_) => 'b',
+            DrawingCommand::Spline(_) => 's',
+            DrawingCommand::ExtendSpline(_) => 'p',
+            DrawingCommand::CloseSpline => 'c',
+        }
+    }
+
+    /// The points of the command.
+    pub fn points(&self) -> &[Point] {
+        match self {
+            DrawingCommand::Move(point) | DrawingCommand::MoveNoClose(point) => std::slice::from_ref(point),
+            DrawingCommand::Line(points)
+            | DrawingCommand::Bezier(points)
+            | DrawingCommand::Spline(points)
+            | DrawingCommand::ExtendSpline(points) => points,
+            DrawingCommand::CloseSpline => &[],
+        }
+    }
+
+    fn points_mut(&mut self) -> &mut [Point] {
+        match self {
+            DrawingCommand::Move(point) | DrawingCommand::MoveNoClose(point) => std::slice::from_mut(point),
+            DrawingCommand::Line(points)
+            | DrawingCommand::Bezier(points)
+            | DrawingCommand::Spline(points)
+            | DrawingCommand::ExtendSpline(points) => points,
+            DrawingCommand::CloseSpline => &mut [],
+        }
+    }
+}
+
+impl Drawing {
+    /// Iterate over every point of the drawing, including the control points of the curves.
+    pub fn points(&self) -> impl Iterator<Item = &Point> {
+        self.commands.iter().flat_map(|command| command.points())
+    }
+
+    fn points_mut(&mut self) -> impl Iterator<Item = &mut Point> {
+        self.commands.iter_mut().flat_map(|command| command.points_mut())
+    }
+
+    /// Move the drawing by `x` and `y`.
+    pub fn translate(&mut self, x: f64, y: f64) -> &mut Self {
+        for point in self.points_mut() {
+            point.x += x;
+            point.y += y;
+        }
+        self
+    }
+
+    /// Scale the drawing from the origin, `1.0` keeps its size.
+    pub fn scale(&mut self, x: f64, y: f64) -> &mut Self {
+        for point in self.points_mut() {
+            point.x *= x;
+            point.y *= y;
+        }
+        self
+    }
+
+    /// Rotate the drawing around `center`, counter-clockwise on screen like `\frz`. [degrees]
+    pub fn rotate(&mut self, degrees: f64, center: Point) -> &mut Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        for point in self.points_mut() {
+            let (x, y) = (point.x - center.x, point.y - center.y);
+            point.x = center.x + x * cos + y * sin;
+            point.y = center.y - x * sin + y * cos;
+        }
+        self
+    }
+
+    /// The top left and bottom right corners of the smallest rectangle containing every point,
+    /// `None` for an empty drawing. The control points of curves are included, so the rectangle
+    /// may be larger than the curves themselves.
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        let mut points = self.points();
+        let first = *points.next()?;
+        Some(points.fold((first, first), |(min, max), point| {
+            (
+                Point::new(min.x.min(point.x), min.y.min(point.y)),
+                Point::new(max.x.max(point.x), max.y.max(point.y)),
+            )
+        }))
+    }
+}
+
+impl FromStr for Drawing {
+    type Err = InvalidDrawing;
+
+    fn from_str(text: &str) -> Result<Drawing, InvalidDrawing> {
+        let invalid = || InvalidDrawing {
+            text: text.to_string(),
+        };
+        let mut commands = Vec::new();
+        let mut name = None;
+        let mut numbers = Vec::new();
+
+        for token in text.split_whitespace() {
+            let number = match token.chars().next() {
+                Some(c) if c.is_ascii_alphabetic() => {
+                    push_command(&mut commands, name, &numbers).ok_or_else(invalid)?;
+                    numbers.clear();
+                    name = Some(c.to_ascii_lowercase());
+                    &token[1..]
+                },
+                _ => token,
+            };
+            if !number.is_empty() {
+                numbers.push(number.parse::<f64>().map_err(|_| invalid())?);
+            }
+        }
+        push_command(&mut commands, name, &numbers).ok_or_else(invalid)?;
+        Ok(Drawing { commands })
+    }
+}
+
+/// Add the command `name` with its coordinates, `None` when they do not fit the command.
+fn push_command(commands: &mut Vec<DrawingCommand>, name: Option<char>, numbers: &[f64]) -> Option<()> {
+    let Some(name) = name else {
+        return numbers.is_empty().then_some(());
+    };
+    if numbers.len() % 2 == 1 {
+        return None;
+    }
+    let points: Vec<Point> = numbers.chunks(2).map(|xy| Point::new(xy[0], xy[1])).collect();
+
+    match name {
+        'm' | 'n' if !points.is_empty() => {
+            let command = if name == 'm' { DrawingCommand::Move } else { DrawingCommand::MoveNoClose };
+            commands.extend(points.into_iter().map(command));
+        },
+        'l' if !points.is_empty() => commands.push(DrawingCommand::Line(points)),
+        'b' if !points.is_empty() && points.chunks(3).all(|curve| curve.len() == 3) => commands.push(DrawingCommand::Bezier(points)),
+        's' if points.len() >= 3 => commands.push(DrawingCommand::Spline(points)),
+        'p' if !points.is_empty() => commands.push(DrawingCommand::ExtendSpline(points)),
+        'c' if points.is_empty() => commands.push(DrawingCommand::CloseSpline),
+        _ => return None,
+    }
+    Some(())
+}
+
+impl fmt::Display for Drawing {
+    /// Coordinates are rounded to three decimals.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, command) in self.commands.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", command.name())?;
+            for point in command.points() {
+                write!(f, " {} {}", round(point.x), round(point.y))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn round(value: f64) -> f64 {
+    // adding 0.0 turns -0 into 0.
+    (value * 1000.0).round() / 1000.0 + 0.0
+}
@@ -19,19 +19,19 @@
 /// Creating a simple `Advanced SubStation Alpha` `(.ass)` file with default values!
 ///
 /// ```rust
-/// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssFileOptions};
+/// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssColor};
 /// use hex_color::HexColor;
 /// 
 /// fn main() {
 ///     let mut ass_file = AssFile::new();
-///     let hexcolor = AssFileOptions::get_ass_color(HexColor::YELLOW);
+///     let hexcolor = AssColor::from(HexColor::YELLOW);
 /// 
 ///     ass_file.components.script
 ///         .set_script(ScriptInfo::default());
 /// 
 ///     ass_file.components.v4
 ///         .set_v4(V4Format::default())
-///         .set_primarycolour(&hexcolor);
+///         .set_primarycolour(hexcolor);
 /// 
 ///     ass_file.components.events
 ///         .set_events(Events::default());
@@ -43,18 +43,17 @@
 /// ```
 /// Here we create an .ass file with default values and When you open the .ass file you can see the
 /// following content.
-/// ```
+/// ```text
+/// [Script Info]
 /// ScriptType: v4.00+
 /// PlayResX: 384
 /// PlayResY: 288
 /// ScaledBorderAndShadow: yes
 /// YCbCr Matrix: None
 /// 
-/// 
 /// [V4+ Styles]
 /// Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
-/// Style: Default,Arial,16,&H00ff,&Hffffff,&H0,&H0,0,0,0,0,100,100,0,0,1,1,0,2,10,10,10,1
-/// 
+/// Style: Default,Arial,16,&H0000FFFF,&H00FFFFFF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,1,0,2,10,10,10,1
 /// 
 /// [Events]
 /// Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
@@ -64,28 +63,28 @@
 /// # Add Dialogues
 ///
 /// ```rust
-/// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssFileOptions, Dialogue};
+/// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssColor, Dialogue, Timestamp};
 /// use ass_parser::IndexNotFound;
 /// use hex_color::HexColor;
 /// 
 /// fn main() -> Result<(), IndexNotFound>{
 ///     let mut ass_file = AssFile::new();
-///     let hexcolor = AssFileOptions::get_ass_color(HexColor::YELLOW);
+///     let hexcolor = AssColor::from(HexColor::YELLOW);
 /// 
 ///     let first_dialogue = Dialogue::default()
 ///         .set_text("Hello There!")
-///         .set_start("0:00:00.10")
-///         .set_end("0:00:00.50");
+///         .set_start(Timestamp::new(0, 0, 0, 10))
+///         .set_end(Timestamp::new(0, 0, 0, 50));
 /// 
 ///     let second_dialogue = Dialogue::default()
 ///         .set_text("Hello Friend!")
-///         .set_start("00:00.50")
-///         .set_end("00:00.58");
+///         .set_start(Timestamp::new(0, 0, 0, 50))
+///         .set_end(Timestamp::new(0, 0, 0, 58));
 /// 
 ///     let third_dialogue = Dialogue::default()
 ///         .set_text("Hello World!!")
-///         .set_start("0:00:00.58")
-///         .set_end("0:00:01.01");
+///         .set_start(Timestamp::new(0, 0, 0, 58))
+///         .set_end(Timestamp::new(0, 0, 1, 1));
 /// 
 ///     let events = Events::new()
 ///         .add_first_dialogue(first_dialogue)?
@@ -100,7 +99,7 @@
 /// 
 ///     ass_file.components.v4
 ///         .set_v4(V4Format::default())
-///         .set_primarycolour(&hexcolor);
+///         .set_primarycolour(hexcolor);
 /// 
 ///     ass_file.components.events
 ///         .set_events(events);
@@ -122,8 +121,8 @@
 ///let random_color:HexColor = rand::random();
 ///
 ///let dialogue = Dialogue::default()
-///    .set_start(&start)
-///    .set_end(&end)
+///    .set_start(*start)
+///    .set_end(*end)
 ///    .set_text(&text)
 ///    .set_colour(random_color);
 ///
@@ -135,18 +134,19 @@
 /// Use the `from_file` function of AssFile to modify and change the contents or appearance. 
 ///
 /// ``` rust
-/// use ass_parser::{AssFile, Dialogue, AssFileOptions};
+/// use ass_parser::{AssFile, Dialogue, AssColor, ParseError};
 /// use hex_color::HexColor;
 /// 
-/// fn main() -> Result<(), std::io::Error>{
+/// fn main() -> Result<(), ParseError>{
 ///     let mut ass_file = AssFile::from_file("subtitles.ass")?;
 ///     let dialogue = Dialogue::default()
 ///         .set_text("Hello Friend!");
-///     let primary_color = AssFileOptions::get_ass_color(HexColor::RED);
+///     let primary_color = AssColor::from(HexColor::RED);
 /// 
 /// 
 ///     ass_file.components.v4
-///         .set_primarycolour(&primary_color);
+///         .get_style_mut("Default")
+///         .map(|style| style.set_primarycolour(primary_color));
 ///         
 ///     ass_file.components.events
 ///         .add_dialogue(dialogue);
@@ -168,13 +168,13 @@
 ///
 /// ```rust
 /// use hex_color::HexColor;
-/// use ass_parser::{AssFile, AssFileOptions};
+/// use ass_parser::{AssFile, AssColor, ParseError};
 /// use ass_parser::{ScriptInfo, V4Format, Events, Dialogue};
 /// use rand;
 /// 
-/// fn main() {
-///     let hexcolor = AssFileOptions::get_ass_color(HexColor::YELLOW);
-///     let srt_file = AssFile::from_srt("RapGod.srt");
+/// fn main() -> Result<(), ParseError> {
+///     let hexcolor = AssColor::from(HexColor::YELLOW);
+///     let srt_file = AssFile::from_srt("RapGod.srt")?;
 ///     let mut ass_file = AssFile::new();
 ///     let mut event = Events::default();
 /// 
@@ -186,8 +186,8 @@
 ///         let random_color:HexColor = rand::random();
 /// 
 ///         let dialogue = Dialogue::default()
-///             .set_start(&start)
-///             .set_end(&end)
+///             .set_start(*start)
+///             .set_end(*end)
 ///             .set_text(&text)
 ///             .set_colour(random_color);
 /// 
@@ -202,34 +202,35 @@
 /// 
 ///     ass_file.components.v4
 ///         .set_v4(V4Format::default())
-///         .set_primarycolour(&hexcolor);
+///         .set_primarycolour(hexcolor);
 ///     ass_file.components.events
 ///         .set_events(event);
 /// 
 ///     AssFile::save_file(&ass_file, "new_subtitle.ass");
+/// 
+///     Ok(())
 /// }
 /// ```
 ///
 ///
 /// ## This will generate an ASS file which would be similiar to this
 ///
-/// ```
+/// ```text
+///[Script Info]
 ///ScriptType: FFMPEG
 ///PlayResX: 384
 ///PlayResY: 288
 ///ScaledBorderAndShadow: yes
 ///YCbCr Matrix: None
 ///
-///
 ///[V4+ Styles]
 ///Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
-///Style: Default,Arial,16,&H0ffff,&Hffffff,&H0,&H0,0,0,0,0,100,100,0,0,1,1,0,2,10,10,10,1
-///
+///Style: Default,Arial,16,&H0000FFFF,&H00FFFFFF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,1,0,2,10,10,10,1
 ///
 ///[Events]
 ///Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
 ///Dialogue: 0,0:00:00.10,0:00:00.50,Default,,0,0,0,,Hello There!
-///Dialogue: 0,00:00.50,00:00.58,Default,,0,0,0,,Hello Friend!
+///Dialogue: 0,0:00:00.50,0:00:00.58,Default,,0,0,0,,Hello Friend!
 ///Dialogue: 0,0:00:00.58,0:00:01.01,Default,,0,0,0,,Hello World!!
 /// ```
 /// # Events can also be created like this
@@ -237,16 +238,16 @@
 ///
 ///```rust
 ///let first_dialogue = Dialogue::default()
-///   .set_start("0:00:00.10")
-///   .set_end("0:00:00.50");
+///   .set_start(Timestamp::new(0, 0, 0, 10))
+///   .set_end(Timestamp::new(0, 0, 0, 50));
 ///
 ///let second_dialogue = Dialogue::default()
-///   .set_start("00:00.50")
-///   .set_end("00:00.58");
+///   .set_start(Timestamp::new(0, 0, 0, 50))
+///   .set_end(Timestamp::new(0, 0, 0, 58));
 ///
 ///let third_dialogue = Dialogue::default()
-///   .set_start("0:00:00.58")
-///   .set_end("0:00:01.01");
+///   .set_start(Timestamp::new(0, 0, 0, 58))
+///   .set_end(Timestamp::new(0, 0, 1, 1));
 ///
 ///let events = Events::new()
 ///   .add_first_dialogue(first_dialogue)?
@@ -279,20 +280,69 @@ use std::ops::Deref;
 use std::fmt;
 use std::iter::Iterator;
 
+mod color;
+mod drawing;
+mod lrc;
+mod microdvd;
 mod parser;
+mod style;
+mod tags;
+mod timecodes;
+mod timestamp;
+mod ttml;
+mod vtt;
+
+pub use color::{AssColor, InvalidColor};
+pub use drawing::{Drawing, DrawingCommand, InvalidDrawing, Point};
+pub use lrc::{Lrc, LrcLine};
+pub use microdvd::{MicroDvd, MicroDvdLine};
+pub use style::{Alignment, BorderStyle, WrapStyle};
+pub use tags::{ClipShape, KaraokeKind, Segment, Tag, TaggedText};
+pub use timecodes::{Keyframes, Timecodes};
+pub use timestamp::{Timestamp, InvalidTimestamp};
+pub use vtt::{CueSettings, VttBlock, VttCue, WebVtt};
 
 type SrtData = parser::SrtContent;
 
 const SCRIPT_HEADER:&str = "[Script Info]";
-const SCRIPT_TYPE:&str = "ScriptType: ";
-const SCRIPT_PLAYRESX:&str = "PlayResX: ";
-const SCRIPT_PLAYRESY:&str = "PlayResY: ";
-const SCRIPT_SCALEDBORDERANDSHADOW:&str =  "ScaledBorderAndShadow: ";
-const SCRIPT_YCBCR_MATRIX:&str =  "YCbCr Matrix: ";
+const SCRIPT_TITLE:&str = "Title";
+const SCRIPT_TYPE:&str = "ScriptType";
+const SCRIPT_WRAPSTYLE:&str = "WrapStyle";
+const SCRIPT_PLAYRESX:&str = "PlayResX";
+const SCRIPT_PLAYRESY:&str = "PlayResY";
+const SCRIPT_LAYOUTRESX:&str = "LayoutResX";
+const SCRIPT_LAYOUTRESY:&str = "LayoutResY";
+const SCRIPT_SCALEDBORDERANDSHADOW:&str =  "ScaledBorderAndShadow";
+const SCRIPT_YCBCR_MATRIX:&str =  "YCbCr Matrix";
 const V4_HEADER:&str = "[V4+ Styles]";
+const SSA_V4_HEADER:&str = "[V4 Styles]";
 const V4_STYLE_HEAD:&str = "Style: ";
 const EVENTS_HEADER:&str = "[Events]";
 const EVENT_HEAD:&str = "Dialogue: ";
+const COMMENT_HEAD:&str = "Comment: ";
+const PICTURE_HEAD:&str = "Picture: ";
+const SOUND_HEAD:&str = "Sound: ";
+const MOVIE_HEAD:&str = "Movie: ";
+const COMMAND_HEAD:&str = "Command: ";
+const FORMAT_HEAD:&str = "Format: ";
+const V4_FORMAT:[&str; 23] = [
+    "Name", "Fontname", "Fontsize", "PrimaryColour", "SecondaryColour", "OutlineColour",
+    "BackColour", "Bold", "Italic", "Underline", "StrikeOut", "ScaleX", "ScaleY", "Spacing",
+    "Angle", "BorderStyle", "Outline", "Shadow", "Alignment", "MarginL", "MarginR", "MarginV",
+    "Encoding",
+];
+const EVENT_FORMAT:[&str; 10] = [
+    "Layer", "Start", "End", "Style", "Name", "MarginL", "MarginR", "MarginV", "Effect", "Text",
+];
+const SSA_V4_FORMAT:[&str; 18] = [
+    "Name", "Fontname", "Fontsize", "PrimaryColour", "SecondaryColour", "TertiaryColour",
+    "BackColour", "Bold", "Italic", "BorderStyle", "Outline", "Shadow", "Alignment", "MarginL",
+    "MarginR", "MarginV", "AlphaLevel", "Encoding",
+];
+const SSA_EVENT_FORMAT:[&str; 10] = [
+    "Marked", "Start", "End", "Style", "Name", "MarginL", "MarginR", "MarginV", "Effect", "Text",
+];
+const SSA_MARKED:&str = "Marked";
 
 
 type Result<T> = std::result::Result<T, IndexNotFound>;
@@ -306,51 +356,172 @@ impl std::fmt::Display for IndexNotFound {
     }
 }
 
+/// Errors returned by `AssFile::from_file` when a file cannot be read or is not a valid
+/// `Advanced SubStation Alpha` file. Line numbers start at 1.
+#[derive(Debug)]
+pub enum ParseError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// A section required by the format, eg. `[Events]`, is not present in the file.
+    MissingSection(String),
+    /// A line depends on a key which is not there, eg. a `Style: ` line without a `Format: ` line
+    /// before it, or a `Format: ` line without a `Text` column.
+    MissingKey {
+        line: usize,
+        key: String,
+        text: String,
+    },
+    /// A `Style: ` or `Dialogue: ` line does not have as many values as its `Format: ` line has
+    /// columns.
+    ColumnCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+        text: String,
+    },
+    /// A start or end time which is not in the `H:MM:SS.cc` format.
+    BadTimestamp {
+        line: usize,
+        text: String,
+    },
+    /// A line of a timecode or keyframe file which is not in the expected format.
+    InvalidLine {
+        line: usize,
+        text: String,
+    },
+    /// The first line of a file does not match any of the supported formats.
+    UnsupportedFormat(String),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "Unable to read the file: {}", e),
+            ParseError::MissingSection(section) => write!(f, "The section {} is missing.", section),
+            ParseError::MissingKey { line, key, text } => {
+                write!(f, "line {}: `{}` is missing for: {}", line, key, text)
+            },
+            ParseError::ColumnCount { line, expected, found, text } => {
+                write!(f, "line {}: expected {} columns but found {}: {}", line, expected, found, text)
+            },
+            ParseError::BadTimestamp { line, text } => {
+                write!(f, "line {}: invalid timestamp: {}", line, text)
+            },
+            ParseError::InvalidLine { line, text } => {
+                write!(f, "line {}: invalid line: {}", line, text)
+            },
+            ParseError::UnsupportedFormat(header) => write!(f, "Unsupported file format: {}", header),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ParseError {
+    fn from(e: std::io::Error) -> ParseError {
+        ParseError::Io(e)
+    }
+}
+
 
 /// The First part of any Advanced SubStation Alpha file is `Script Info`.
 /// This holds necessary information which include the version the resolution of subtitles etc of
 /// the `.ass` file.
+///
+/// Every `Key: value` pair is kept in the order of the file, including the keys this crate has
+/// no accessor for (`Original Script`, `Timer`, `Collisions`...), and so are the comments.
+///
+/// ```rust
+/// use ass_parser::{ScriptInfo, WrapStyle};
+///
+/// let mut script = ScriptInfo::default();
+/// script.set_value("Original Script", "Someone")
+///     .set_wrapstyle(WrapStyle::None)
+///     .set_playresx(1920);
+///
+/// assert_eq!(Some("Someone"), script.get_value("original script"));
+/// assert_eq!(Some(WrapStyle::None), script.get_wrapstyle());
+/// assert_eq!(Some(1920), script.get_playresx());
+/// assert_eq!(Some(true), script.get_scaledborderandshadow());
+/// ```
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct ScriptInfo {
-    scripttype: Option<String>,
-    playresx: Option<String>,
-    playresy: Option<String>,
-    scaledborderandshadow: Option<String>,
-    ycbcr_matrix: Option<String>,
+    lines: Vec<ScriptLine>,
+}
+
+/// A single line of the `[Script Info]` section.
+#[derive(Debug, PartialEq, Clone)]
+enum ScriptLine {
+    /// a `Key: value` pair, with the `:` and the whitespace after it kept as they were in the
+    /// file.
+    Pair(String, String, String),
+    /// a comment, a blank line or anything else which is not a `Key: value` pair.
+    Raw(String),
 }
 
 impl ScriptInfo {
-    fn get_key_values(&self) -> Vec<[&str; 2]> {
-        let mut values = Vec::new();
+    /// get the value of `key`. Keys are matched case insensitively.
+    pub fn get_value(&self, key: &str) -> Option<&str> {
+        self.iter()
+            .find(|(k, _)| k.trim().eq_ignore_ascii_case(key.trim()))
+            .map(|(_, value)| value)
+    }
 
-        if let Some(value) = &self.scripttype {
-            values.push([SCRIPT_TYPE, value])
-        }
-        if let Some(value) = &self.playresx {
-            values.push([SCRIPT_PLAYRESX, value])
-        }
-        if let Some(value) = &self.playresy{
-            values.push([SCRIPT_PLAYRESY, value])
-        }
-        if let Some(value) = &self.scaledborderandshadow {
-            values.push([SCRIPT_SCALEDBORDERANDSHADOW, value])
+    /// Replace the value of `key`, or add it after the last `Key: value` pair when it is not
+    /// present yet.
+    pub fn set_value(&mut self, key: &str, value: &str) -> &mut Self {
+        for line in self.lines.iter_mut() {
+            if let ScriptLine::Pair(k, separator, v) = line {
+                if k.trim().eq_ignore_ascii_case(key.trim()) {
+                    *separator = ": ".to_string();
+                    *v = value.to_string();
+                    return self;
+                }
+            }
         }
-        if let Some(value) = &self.ycbcr_matrix {
-            values.push([SCRIPT_YCBCR_MATRIX, value])
+        let index = self.lines.iter()
+            .rposition(|line| matches!(line, ScriptLine::Pair(..)))
+            .map_or(self.lines.len(), |i| i + 1);
+        self.lines.insert(index, ScriptLine::Pair(key.to_string(), ": ".to_string(), value.to_string()));
+        self
+    }
+
+    /// Remove `key` and return its value.
+    pub fn remove_value(&mut self, key: &str) -> Option<String> {
+        let index = self.lines.iter().position(|line| {
+            matches!(line, ScriptLine::Pair(k, _, _) if k.trim().eq_ignore_ascii_case(key.trim()))
+        })?;
+        match self.lines.remove(index) {
+            ScriptLine::Pair(_, _, value) => Some(value),
+            ScriptLine::Raw(_) => None,
         }
-        values
+    }
+
+    /// Iterate over the `Key: value` pairs in the order of the file.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.lines.iter().filter_map(|line| match line {
+            ScriptLine::Pair(key, _, value) => Some((key.as_str(), value.as_str())),
+            ScriptLine::Raw(_) => None,
+        })
+    }
+
+    fn get_number(&self, key: &str) -> Option<u32> {
+        self.get_value(key)?.trim().parse().ok()
     }
 }
 
 impl ScriptInfo {
     fn new() -> Self {
         Self {
-    		scripttype: None,
-    		playresx: None,
-    		playresy: None,
-    		scaledborderandshadow: None,
-    		ycbcr_matrix: None,
+            lines: Vec::new(),
         }
     }
 
@@ -362,13 +533,13 @@ impl ScriptInfo {
 
 impl Default for ScriptInfo {
     fn default() -> ScriptInfo {
-        ScriptInfo {
-    		scripttype: Some("v4.00+".to_string()),
-    		playresx: Some("384".to_string()),
-    		playresy: Some("288".to_string()),
-    		scaledborderandshadow: Some("yes".to_string()),
-    		ycbcr_matrix: Some("None".to_string()),
-        }
+        ScriptInfo::new()
+            .set_scripttype("v4.00+")
+            .set_playresx(384)
+            .set_playresy(288)
+            .set_scaledborderandshadow(true)
+            .set_ycbcr_matrix("None")
+            .clone()
     }
 }
 
@@ -380,8 +551,7 @@ impl ScriptInfo {
     /// you are using a version of SSA older than the version that created the script.
     /// ASS version is “V4.00+”.
     pub fn set_scripttype(&mut self, value: &str) -> &mut Self {
-		self.scripttype = Some(value.to_string());
-		self
+		self.set_value(SCRIPT_TYPE, value)
 	}
     /// After creating the `AssFile` set the playresx of the .ass file.
     ///
@@ -389,9 +559,8 @@ impl ScriptInfo {
     ///
     /// If you don't want to specify any, the default playresx from the original `.ass` file will be
     /// used.
-    pub fn set_playresx(&mut self, value: &str) -> &mut Self {
-		self.playresx = Some(value.to_string());
-		self
+    pub fn set_playresx(&mut self, value: u32) -> &mut Self {
+		self.set_value(SCRIPT_PLAYRESX, &value.to_string())
 	}
     /// After creating the `AssFile` set the playresy of the .ass file.
     ///
@@ -400,24 +569,85 @@ impl ScriptInfo {
     /// If you want to specify any, the default playresy from the original `.ass` file will be
     /// used.
     /// 
-    pub fn set_playresy(&mut self, value: &str) -> &mut Self {
-		self.playresy = Some(value.to_string());
-		self
+    pub fn set_playresy(&mut self, value: u32) -> &mut Self {
+		self.set_value(SCRIPT_PLAYRESY, &value.to_string())
 	}
     /// After creating the `AssFile` set the scaledborderandshadow of the .ass file.
     /// If you want to specify any, the default scaledborderandshadowfrom the original `.ass` file will be
     /// used.
-    pub fn set_scaledborderandshadow(&mut self, value: &str) -> &mut Self {
-		self.scaledborderandshadow = Some(value.to_string());
-		self
+    /// `true` is written as `yes`, `false` as `no`.
+    pub fn set_scaledborderandshadow(&mut self, value: bool) -> &mut Self {
+		self.set_value(SCRIPT_SCALEDBORDERANDSHADOW, if value { "yes" } else { "no" })
 	}
     /// After creating the `AssFile` set the ycbcr_matrix( of the .ass file.
     /// If you want to specify any, the default ycbcr_matrix from the original `.ass` file will be
     /// used.
     pub fn set_ycbcr_matrix(&mut self, value: &str) -> &mut Self {
-		self.ycbcr_matrix = Some(value.to_string());
-		self
+		self.set_value(SCRIPT_YCBCR_MATRIX, value)
 	}
+    /// set the title of the script.
+    pub fn set_title(&mut self, value: &str) -> &mut Self {
+		self.set_value(SCRIPT_TITLE, value)
+	}
+    /// set how long lines are broken.
+    pub fn set_wrapstyle(&mut self, value: WrapStyle) -> &mut Self {
+		self.set_value(SCRIPT_WRAPSTYLE, &value.value().to_string())
+	}
+    /// set the width of the video the script was laid out for, used with `PlayResX` to scale
+    /// `\pos` and similar tags when the video is resampled.
+    pub fn set_layoutresx(&mut self, value: u32) -> &mut Self {
+		self.set_value(SCRIPT_LAYOUTRESX, &value.to_string())
+	}
+    /// set the height of the video the script was laid out for.
+    pub fn set_layoutresy(&mut self, value: u32) -> &mut Self {
+		self.set_value(SCRIPT_LAYOUTRESY, &value.to_string())
+	}
+}
+
+impl ScriptInfo {
+    /// get the ScriptType of the .ass file, eg. `v4.00+`.
+    pub fn get_scripttype(&self) -> Option<String> {
+        self.get_value(SCRIPT_TYPE).map(str::to_string)
+    }
+    /// get the title of the script.
+    pub fn get_title(&self) -> Option<String> {
+        self.get_value(SCRIPT_TITLE).map(str::to_string)
+    }
+    /// get the width of the screen the positions of the script are relative to.
+    pub fn get_playresx(&self) -> Option<u32> {
+        self.get_number(SCRIPT_PLAYRESX)
+    }
+    /// get the height of the screen the positions of the script are relative to.
+    pub fn get_playresy(&self) -> Option<u32> {
+        self.get_number(SCRIPT_PLAYRESY)
+    }
+    /// get the width of the video the script was laid out for.
+    pub fn get_layoutresx(&self) -> Option<u32> {
+        self.get_number(SCRIPT_LAYOUTRESX)
+    }
+    /// get the height of the video the script was laid out for.
+    pub fn get_layoutresy(&self) -> Option<u32> {
+        self.get_number(SCRIPT_LAYOUTRESY)
+    }
+    /// get whether borders and shadows are scaled with the video. `None` if the value is
+    /// neither `yes` nor `no`.
+    pub fn get_scaledborderandshadow(&self) -> Option<bool> {
+        match self.get_value(SCRIPT_SCALEDBORDERANDSHADOW)?.trim() {
+            value if value.eq_ignore_ascii_case("yes") => Some(true),
+            value if value.eq_ignore_ascii_case("no") => Some(false),
+            _ => None,
+        }
+    }
+    /// get how long lines are broken.
+    pub fn get_wrapstyle(&self) -> Option<WrapStyle> {
+        self.get_number(SCRIPT_WRAPSTYLE)
+            .and_then(|value| u8::try_from(value).ok())
+            .and_then(WrapStyle::from_value)
+    }
+    /// get the YCbCr Matrix of the .ass file, eg. `TV.709`.
+    pub fn get_ycbcr_matrix(&self) -> Option<String> {
+        self.get_value(SCRIPT_YCBCR_MATRIX).map(str::to_string)
+    }
 }
 
 
@@ -426,6 +656,9 @@ impl ScriptInfo {
 /// The Second part of any Advanced SubStation Alpha file is `V4Format`.
 /// This is the part which has fields separated by comma which specify the format, styling,
 /// encoding colors and many other important parts of the the `.ass` file.
+///
+/// The setters and getters are typed, eg. `set_bold(true)` writes `-1`. Use `get_field` and
+/// `set_field` to read or write a value the typed accessors can not represent.
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct V4Format {
@@ -452,6 +685,8 @@ pub struct V4Format {
     marginr: Option<String>,
     marginv: Option<String>,
     encoding: Option<String>,
+    /// columns of the `Format: ` line which are not part of the `[V4+ Styles]` format.
+    extra: Vec<(String, String)>,
 }
 
 impl V4Format {
@@ -480,6 +715,7 @@ impl V4Format {
             marginr: None,
             marginv: None,
             encoding: None,
+            extra: Vec::new(),
         }
     }
 }
@@ -491,10 +727,10 @@ impl Default for V4Format {
         name: Some("Default".to_string()),
         fontname: Some("Arial".to_string()),
         fontsize: Some("16".to_string()),
-        primarycolour: Some("&Hffffff".to_string()),
-        secondarycolour: Some("&Hffffff".to_string()),
-        outlinecolour:Some("&H0".to_string()),
-        backcolour: Some("&H0".to_string()),
+        primarycolour: Some(AssColor::rgb(255, 255, 255).to_string()),
+        secondarycolour: Some(AssColor::rgb(255, 255, 255).to_string()),
+        outlinecolour: Some(AssColor::rgb(0, 0, 0).to_string()),
+        backcolour: Some(AssColor::rgb(0, 0, 0).to_string()),
         bold: Some("0".to_string()),
         italic: Some("0".to_string()),
         underline: Some("0".to_string()),
@@ -511,6 +747,7 @@ impl Default for V4Format {
         marginr: Some("10".to_string()),
         marginv: Some("10".to_string()),
         encoding: Some("1".to_string()),
+        extra: Vec::new(),
         }
     }
 }
@@ -549,6 +786,63 @@ impl V4Format {
             ]
     }
 
+    fn get_array_mut(&mut self) -> [&mut Option<String>; 23] {
+        [
+            &mut self.name,
+            &mut self.fontname,
+            &mut self.fontsize,
+            &mut self.primarycolour,
+            &mut self.secondarycolour,
+            &mut self.outlinecolour,
+            &mut self.backcolour,
+            &mut self.bold,
+            &mut self.italic,
+            &mut self.underline,
+            &mut self.strikeout,
+            &mut self.scalex,
+            &mut self.scaley,
+            &mut self.spacing,
+            &mut self.angle,
+            &mut self.borderstyle,
+            &mut self.outline,
+            &mut self.shadow,
+            &mut self.alignment,
+            &mut self.marginl,
+            &mut self.marginr,
+            &mut self.marginv,
+            &mut self.encoding,
+            ]
+    }
+
+    fn column_index(column: &str) -> Option<usize> {
+        V4_FORMAT.iter().position(|name| name.eq_ignore_ascii_case(column.trim()))
+    }
+
+    /// get the raw value of a column by the name used in the `Format: ` line.
+    /// Column names are matched case insensitively. Columns which are not part of the
+    /// `[V4+ Styles]` format (eg. `TertiaryColour` or `AlphaLevel` of SSA v4 files) are kept as
+    /// they were read.
+    pub fn get_field(&self, column: &str) -> Option<String> {
+        match Self::column_index(column) {
+            Some(i) => self.get_array()[i].clone(),
+            None => self.extra.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(column.trim()))
+                .map(|(_, value)| value.clone()),
+        }
+    }
+
+    /// set the raw value of a column by the name used in the `Format: ` line.
+    pub fn set_field(&mut self, column: &str, value: &str) -> &mut Self {
+        match Self::column_index(column) {
+            Some(i) => *self.get_array_mut()[i] = Some(value.to_string()),
+            None => match self.extra.iter_mut().find(|(name, _)| name.eq_ignore_ascii_case(column.trim())) {
+                Some(extra) => extra.1 = value.to_string(),
+                None => self.extra.push((column.trim().to_string(), value.to_string())),
+            },
+        }
+        self
+    }
+
 }
 
 impl V4Format {
@@ -567,27 +861,26 @@ impl V4Format {
         self.fontname = Some(value.to_string());
         self
 	}
-    /// set the fontsize for the V4 field.
+    /// set the fontsize for the V4 field. [points]
 	pub fn set_fontsize(&mut self,
-                        value: &str) -> &mut Self{
+                        value: f64) -> &mut Self{
         self.fontsize = Some(value.to_string());
         self
 	}
     /// set the primarycolour for the V4 field.
     /// ```rust
-    /// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssFileOptions};
+    /// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssColor};
     /// use hex_color::HexColor;
     /// 
     /// fn main() {
     ///     let mut ass_file = AssFile::new();
-    ///     let hexcolor = AssFileOptions::get_ass_color(HexColor::YELLOW);
     /// 
     ///     ass_file.components.script
     ///         .set_script(ScriptInfo::default());
     /// 
     ///     ass_file.components.v4
     ///         .set_v4(V4Format::default())
-    ///         .set_primarycolour(&hexcolor);
+    ///         .set_primarycolour(HexColor::YELLOW);
     /// 
     ///     ass_file.components.events
     ///         .set_events(Events::default());
@@ -596,25 +889,24 @@ impl V4Format {
     /// }
     /// ```
 	pub fn set_primarycolour(&mut self,
-                             value: &str) -> &mut Self{
-        self.primarycolour = Some(value.to_string());
+                             value: impl Into<AssColor>) -> &mut Self{
+        self.primarycolour = Some(value.into().to_string());
         self
 	}
     /// set the secondarycolour for the V4 field.
     /// ```rust
-    /// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssFileOptions};
+    /// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssColor};
     /// use hex_color::HexColor;
     /// 
     /// fn main() {
     ///     let mut ass_file = AssFile::new();
-    ///     let hexcolor = AssFileOptions::get_ass_color(HexColor::YELLOW);
     /// 
     ///     ass_file.components.script
     ///         .set_script(ScriptInfo::default());
     /// 
     ///     ass_file.components.v4
     ///         .set_v4(V4Format::default())
-    ///         .set_secondarycolour(&hexcolor);
+    ///         .set_secondarycolour(HexColor::YELLOW);
     /// 
     ///     ass_file.components.events
     ///         .set_events(Events::default());
@@ -623,25 +915,24 @@ impl V4Format {
     /// }
     /// ```
 	pub fn set_secondarycolour(&mut self,
-                               value: &str) -> &mut Self{
-        self.secondarycolour = Some(value.to_string());
+                               value: impl Into<AssColor>) -> &mut Self{
+        self.secondarycolour = Some(value.into().to_string());
         self
 	}
     /// set the outlinecolour for the V4 field.
     /// ```rust
-    /// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssFileOptions};
+    /// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssColor};
     /// use hex_color::HexColor;
     /// 
     /// fn main() {
     ///     let mut ass_file = AssFile::new();
-    ///     let hexcolor = AssFileOptions::get_ass_color(HexColor::YELLOW);
     /// 
     ///     ass_file.components.script
     ///         .set_script(ScriptInfo::default());
     /// 
     ///     ass_file.components.v4
     ///         .set_v4(V4Format::default())
-    ///         .set_outlinecolour(&hexcolor);
+    ///         .set_outlinecolour(HexColor::YELLOW);
     /// 
     ///     ass_file.components.events
     ///         .set_events(Events::default());
@@ -650,25 +941,24 @@ impl V4Format {
     /// }
     /// ```
 	pub fn set_outlinecolour(&mut self,
-                             value: &str) -> &mut Self{
-        self.outlinecolour = Some(value.to_string());
+                             value: impl Into<AssColor>) -> &mut Self{
+        self.outlinecolour = Some(value.into().to_string());
         self
 	}
     /// set the backcolour for the V4 field.
     /// ```rust
-    /// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssFileOptions};
+    /// use ass_parser::{self, AssFile, ScriptInfo, V4Format, Events, AssColor};
     /// use hex_color::HexColor;
     /// 
     /// fn main() {
     ///     let mut ass_file = AssFile::new();
-    ///     let hexcolor = AssFileOptions::get_ass_color(HexColor::YELLOW);
     /// 
     ///     ass_file.components.script
     ///         .set_script(ScriptInfo::default());
     /// 
     ///     ass_file.components.v4
     ///         .set_v4(V4Format::default())
-    ///         .set_backcolour(&hexcolor);
+    ///         .set_backcolour(HexColor::YELLOW);
     /// 
     ///     ass_file.components.events
     ///         .set_events(Events::default());
@@ -677,107 +967,107 @@ impl V4Format {
     /// }
     /// ```
 	pub fn set_backcolour(&mut self,
-                          value: &str) -> &mut Self{
-        self.backcolour = Some(value.to_string());
+                          value: impl Into<AssColor>) -> &mut Self{
+        self.backcolour = Some(value.into().to_string());
         self
 	}
     /// set the bold for the V4 field.
-    /// This defines whether text is bold (true) or not (false). -1 is True, 0 is False. This is independant of the Italic attribute - you can have have text which is both bold and italic
+    /// This defines whether text is bold (true) or not (false). true is written as -1, false as 0. This is independant of the Italic attribute - you can have have text which is both bold and italic
 	pub fn set_bold(&mut self,
-                    value: &str) -> &mut Self{
-        self.bold = Some(value.to_string());
+                    value: bool) -> &mut Self{
+        self.bold = Some(bool_value(value));
         self
 	}
     /// set the italic for the V4 field.
-    /// This defines whether text is italic (true) or not (false). -1 is True, 0 is False. This is independant of the bold attribute - you can have have text which is both bold and italic.
+    /// This defines whether text is italic (true) or not (false). true is written as -1, false as 0. This is independant of the bold attribute - you can have have text which is both bold and italic.
 	pub fn set_italic(&mut self,
-                      value: &str) -> &mut Self{
-        self.italic = Some(value.to_string());
+                      value: bool) -> &mut Self{
+        self.italic = Some(bool_value(value));
         self
 	}
     /// set the underline for the V4 field.
-    ///  use either of [-1 or 0] where -1 is considered True and 0 is considered False.
+    /// true is written as -1, false as 0.
 	pub fn set_underline(&mut self,
-                         value: &str) -> &mut Self{
-        self.underline = Some(value.to_string());
+                         value: bool) -> &mut Self{
+        self.underline = Some(bool_value(value));
         self
 	}
     /// set the strikeout for the V4 field.
-    ///  use either of [-1 or 0] where -1 is considered True and 0 is considered False.
+    /// true is written as -1, false as 0.
 	pub fn set_strikeout(&mut self,
-                         value: &str) -> &mut Self{
-        self.strikeout = Some(value.to_string());
+                         value: bool) -> &mut Self{
+        self.strikeout = Some(bool_value(value));
         self
 	}
     /// set the scalex for the V4 field.
     /// ScaleX. Modifies the width of the font. [percent]
 	pub fn set_scalex(&mut self,
-                      value: &str) -> &mut Self{
+                      value: f64) -> &mut Self{
         self.scalex = Some(value.to_string());
         self
 	}
     /// set the scaley for the V4 field.
     /// ScaleX. Modifies the height of the font. [percent]
 	pub fn set_scaley(&mut self,
-                      value: &str) -> &mut Self{
+                      value: f64) -> &mut Self{
         self.scaley = Some(value.to_string());
         self
 	}
     /// set the spacing for the V4 field.
     ///  Extra space between characters. [pixels]
 	pub fn set_spacing(&mut self,
-                       value: &str) -> &mut Self{
+                       value: f64) -> &mut Self{
         self.spacing = Some(value.to_string());
         self
 	}
     /// set the angle for the V4 field.
     /// The origin of the rotation is defined by the alignment. Can be a floating point number. [degrees]
 	pub fn set_angle(&mut self,
-                     value: &str) -> &mut Self{
+                     value: f64) -> &mut Self{
         self.angle = Some(value.to_string());
         self
 	}
     /// set the borderstyle for the V4 field.
-    ///  pass either 1 or 3. where 1=Outline + drop shadow, 3=Opaque box.
+    /// `BorderStyle::Outline` (1) is an outline + drop shadow, `BorderStyle::OpaqueBox` (3) an opaque box.
 	pub fn set_borderstyle(&mut self,
-                           value: &str) -> &mut Self{
-        self.borderstyle = Some(value.to_string());
+                           value: BorderStyle) -> &mut Self{
+        self.borderstyle = Some(value.value().to_string());
         self
 	}
     /// set the outline for the V4 field.
     /// If BorderStyle is 1,  then this specifies the width of the outline around the text, in pixels.
     /// Values may be 0, 1, 2, 3 or 4.
 	pub fn set_outline(&mut self,
-                       value: &str) -> &mut Self{
+                       value: f64) -> &mut Self{
         self.outline = Some(value.to_string());
         self
 	}
     /// set the shadow for the V4 field.
     /// If BorderStyle is 1,  then this specifies the depth of the drop shadow behind the text, in pixels. Values may be 0, 1, 2, 3 or 4. Drop shadow is always used in addition to an outline. 
 	pub fn set_shadow(&mut self,
-                      value: &str) -> &mut Self{
+                      value: f64) -> &mut Self{
         self.shadow = Some(value.to_string());
         self
 	}
     /// set the alignment for the V4 field.
-    /// This sets how text is "justified" within the Left/Right onscreen margins, and also the vertical placing. Values may be 1=Left, 2=Centered, 3=Right. Add 4 to the value for a "Toptitle". Add 8 to the value for a "Midtitle".
-    /// eg. 5 = left-justified toptitle
+    /// This sets how text is "justified" within the Left/Right onscreen margins, and also the vertical placing.
+    /// The value written is the position on a numpad, eg. `Alignment::TopLeft` is 7.
 	pub fn set_alignment(&mut self,
-                         value: &str) -> &mut Self{
-        self.alignment = Some(value.to_string());
+                         value: Alignment) -> &mut Self{
+        self.alignment = Some(value.numpad().to_string());
         self
 	}
     /// set the marginl for the V4 field.
     /// This defines the Left Margin in pixels. It is the distance from the left-hand edge of the screen.The three onscreen margins (MarginL, MarginR, MarginV) define areas in which the subtitle text will be displayed.
 	pub fn set_marginl(&mut self,
-                       value: &str) -> &mut Self{
+                       value: i32) -> &mut Self{
         self.marginl = Some(value.to_string());
         self
 	}
     /// set the marginr for the V4 field.
     /// This defines the Right Margin in pixels. It is the distance from the right-hand edge of the screen. The three onscreen margins (MarginL, MarginR, MarginV) define areas in which the subtitle text will be displayed.
 	pub fn set_marginr(&mut self,
-                       value: &str) -> &mut Self{
+                       value: i32) -> &mut Self{
         self.marginr = Some(value.to_string());
         self
 	}
@@ -787,18 +1077,318 @@ impl V4Format {
     /// For a toptitle, it is the distance from the top of the screen.
     /// For a midtitle, the value is ignored - the text will be vertically centred.
 	pub fn set_marginv(&mut self,
-                       value: &str) -> &mut Self{
+                       value: i32) -> &mut Self{
         self.marginv = Some(value.to_string());
         self
 	}
     /// set the encoding for the V4 field.
     /// This specifies the font character set or encoding and on multi-lingual Windows installations it provides access to characters used in multiple than one languages. It is usually 0 (zero) for English (Western, ANSI) Windows.
-	fn set_encoding(&mut self, value: &str) -> &mut Self{
+	pub fn set_encoding(&mut self, value: i32) -> &mut Self{
         self.encoding = Some(value.to_string());
         self
 	}
 }
 
+impl V4Format {
+    /// get the name of the Style.
+    pub fn get_name(&self) -> Option<String> {
+        return self.name.clone();
+    }
+    /// get the primarycolour of the Style, `None` if it is missing or not a valid colour.
+    pub fn get_primarycolour(&self) -> Option<AssColor> {
+        self.primarycolour.as_deref()?.parse().ok()
+    }
+    /// get the secondarycolour of the Style, `None` if it is missing or not a valid colour.
+    pub fn get_secondarycolour(&self) -> Option<AssColor> {
+        self.secondarycolour.as_deref()?.parse().ok()
+    }
+    /// get the outlinecolour of the Style, `None` if it is missing or not a valid colour.
+    pub fn get_outlinecolour(&self) -> Option<AssColor> {
+        self.outlinecolour.as_deref()?.parse().ok()
+    }
+    /// get the backcolour of the Style, `None` if it is missing or not a valid colour.
+    pub fn get_backcolour(&self) -> Option<AssColor> {
+        self.backcolour.as_deref()?.parse().ok()
+    }
+    /// get the fontname of the Style.
+    pub fn get_fontname(&self) -> Option<String> {
+        self.fontname.clone()
+    }
+    /// get the fontsize of the Style.
+    pub fn get_fontsize(&self) -> Option<f64> {
+        parse_value(&self.fontsize)
+    }
+    /// get whether the Style is bold. Any value other than 0 is considered true.
+    pub fn get_bold(&self) -> Option<bool> {
+        parse_bool(&self.bold)
+    }
+    /// get whether the Style is italic. Any value other than 0 is considered true.
+    pub fn get_italic(&self) -> Option<bool> {
+        parse_bool(&self.italic)
+    }
+    /// get whether the Style is underlined. Any value other than 0 is considered true.
+    pub fn get_underline(&self) -> Option<bool> {
+        parse_bool(&self.underline)
+    }
+    /// get whether the Style is striked out. Any value other than 0 is considered true.
+    pub fn get_strikeout(&self) -> Option<bool> {
+        parse_bool(&self.strikeout)
+    }
+    /// get the scalex of the Style. [percent]
+    pub fn get_scalex(&self) -> Option<f64> {
+        parse_value(&self.scalex)
+    }
+    /// get the scaley of the Style. [percent]
+    pub fn get_scaley(&self) -> Option<f64> {
+        parse_value(&self.scaley)
+    }
+    /// get the spacing of the Style. [pixels]
+    pub fn get_spacing(&self) -> Option<f64> {
+        parse_value(&self.spacing)
+    }
+    /// get the angle of the Style. [degrees]
+    pub fn get_angle(&self) -> Option<f64> {
+        parse_value(&self.angle)
+    }
+    /// get the borderstyle of the Style.
+    pub fn get_borderstyle(&self) -> Option<BorderStyle> {
+        parse_value(&self.borderstyle).and_then(BorderStyle::from_value)
+    }
+    /// get the outline width of the Style. [pixels]
+    pub fn get_outline(&self) -> Option<f64> {
+        parse_value(&self.outline)
+    }
+    /// get the shadow depth of the Style. [pixels]
+    pub fn get_shadow(&self) -> Option<f64> {
+        parse_value(&self.shadow)
+    }
+    /// get the alignment of the Style.
+    pub fn get_alignment(&self) -> Option<Alignment> {
+        parse_value(&self.alignment).and_then(Alignment::from_numpad)
+    }
+    /// get the marginl of the Style. [pixels]
+    pub fn get_marginl(&self) -> Option<i32> {
+        parse_value(&self.marginl)
+    }
+    /// get the marginr of the Style. [pixels]
+    pub fn get_marginr(&self) -> Option<i32> {
+        parse_value(&self.marginr)
+    }
+    /// get the marginv of the Style. [pixels]
+    pub fn get_marginv(&self) -> Option<i32> {
+        parse_value(&self.marginv)
+    }
+    /// get the encoding of the Style.
+    pub fn get_encoding(&self) -> Option<i32> {
+        parse_value(&self.encoding)
+    }
+}
+
+impl V4Format {
+    /// Turn a style read from a `[V4 Styles]` section into a `[V4+ Styles]` one. `TertiaryColour`
+    /// becomes the outline colour, the alignment is renumbered and the columns SSA does not have
+    /// get their default value. `AlphaLevel`, which renderers ignore, is dropped.
+    fn upgrade_ssa(&mut self) {
+        let mut extra = std::mem::take(&mut self.extra);
+        if let Some(i) = extra.iter().position(|(name, _)| name.eq_ignore_ascii_case("TertiaryColour")) {
+            self.outlinecolour = Some(extra.remove(i).1);
+        }
+        extra.retain(|(name, _)| !name.eq_ignore_ascii_case("AlphaLevel"));
+        self.extra = extra;
+
+        for colour in [&mut self.primarycolour, &mut self.secondarycolour, &mut self.outlinecolour, &mut self.backcolour] {
+            if let Some(value) = colour.as_deref().and_then(|value| value.parse::<AssColor>().ok()) {
+                *colour = Some(value.to_string());
+            }
+        }
+        if let Some(alignment) = parse_value(&self.alignment).and_then(Alignment::from_ssa) {
+            self.alignment = Some(alignment.numpad().to_string());
+        }
+        let default = V4Format::default();
+        for (value, default) in self.get_array_mut().into_iter().zip(default.get_array()) {
+            if value.is_none() {
+                *value = default.clone();
+            }
+        }
+    }
+
+    /// The style with the columns of a `[V4 Styles]` section: colours as decimal numbers, the
+    /// outline colour as `TertiaryColour` and the SSA numbering of the alignment.
+    fn downgrade_ssa(&self) -> V4Format {
+        let mut style = self.clone();
+        for colour in [&mut style.primarycolour, &mut style.secondarycolour, &mut style.outlinecolour, &mut style.backcolour] {
+            if let Some(value) = colour.as_deref().and_then(|value| value.parse::<AssColor>().ok()) {
+                *colour = Some(value.to_ssa());
+            }
+        }
+        if let Some(alignment) = self.get_alignment() {
+            style.alignment = Some(alignment.ssa().to_string());
+        }
+        let tertiary = style.outlinecolour.clone().unwrap_or_default();
+        style.set_field("TertiaryColour", &tertiary).set_field("AlphaLevel", "0");
+        style
+    }
+}
+
+/// Parse a numeric column, `None` if it is missing or not a number.
+fn parse_value<T: std::str::FromStr>(value: &Option<String>) -> Option<T> {
+    value.as_deref()?.trim().parse().ok()
+}
+
+/// Parse a `-1`/`0` column.
+fn parse_bool(value: &Option<String>) -> Option<bool> {
+    parse_value::<i32>(value).map(|value| value != 0)
+}
+
+/// The `-1`/`0` form of a boolean used by `.ass` files.
+fn bool_value(value: bool) -> String {
+    if value { "-1" } else { "0" }.to_string()
+}
+
+
+/// # Styles
+///
+/// The `[V4+ Styles]` section of an `Advanced SubStation Alpha` file.
+/// A file can define any number of styles ("Default", "Signs", "Top"...). `Styles` keeps every
+/// one of them as a `V4Format` in the order they appear in the file, and a `Dialogue` refers to
+/// one of them by its name.
+///
+/// ```rust
+/// use ass_parser::{Styles, V4Format};
+///
+/// let mut styles = Styles::new();
+/// styles.add_style(V4Format::default());
+/// styles.add_style(V4Format::default().set_name("Signs").clone())
+///     .set_fontname("Verdana");
+///
+/// styles.rename_style("Signs", "Top");
+/// assert!(styles.get_style("Top").is_some());
+/// assert_eq!(styles.len(), 2);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Styles {
+    format: Vec<String>,
+    styles: Vec<V4Format>,
+    /// comments and other lines of the section which are not styles, along with the number of
+    /// `Format: ` and `Style: ` lines written before them.
+    raw_lines: Vec<(usize, String)>,
+    /// the `Format: ` and `Style: ` lines which were not read the way they are written, like
+    /// `Style:Default,...` without a space, with the line they are written as. They are written
+    /// back as they were read as long as they are not modified.
+    verbatim: Vec<(String, String)>,
+}
+
+impl Styles {
+    /// Create an empty `Styles` section.
+    pub fn new() -> Styles {
+        Styles {
+            format: format_columns(&V4_FORMAT),
+            styles: Vec::new(),
+            raw_lines: Vec::new(),
+            verbatim: Vec::new(),
+        }
+    }
+
+    /// Replace every style with a single `V4Format` and return it so that it can be modified
+    /// further.
+    pub fn set_v4(&mut self, v4: V4Format) -> &mut V4Format {
+        self.styles = vec![v4];
+        &mut self.styles[0]
+    }
+
+    /// Set Styles from a Styles Struct.
+    pub fn set_styles(&mut self, styles: Styles) -> &mut Styles {
+        *self = styles;
+        self
+    }
+
+    /// Add a style to the end of the section.
+    /// If a style with the same name already exists it is replaced in place instead.
+    pub fn add_style(&mut self, style: V4Format) -> &mut V4Format {
+        let index = match self.position(&style.get_name().unwrap_or_default()) {
+            Some(i) => {
+                self.styles[i] = style;
+                i
+            },
+            None => {
+                self.styles.push(style);
+                self.styles.len() - 1
+            }
+        };
+        &mut self.styles[index]
+    }
+
+    /// get the style with the given name. Style names are case sensitive.
+    pub fn get_style(&self, name: &str) -> Option<&V4Format> {
+        self.position(name).map(|i| &self.styles[i])
+    }
+
+    /// get a mutable reference to the style with the given name.
+    pub fn get_style_mut(&mut self, name: &str) -> Option<&mut V4Format> {
+        self.position(name).map(|i| &mut self.styles[i])
+    }
+
+    /// Remove the style with the given name and return it.
+    pub fn remove_style(&mut self, name: &str) -> Option<V4Format> {
+        self.position(name).map(|i| self.styles.remove(i))
+    }
+
+    /// Rename a style. Returns `None` if there is no style called `old`.
+    /// Dialogues referring to the old name are left untouched.
+    pub fn rename_style(&mut self, old: &str, new: &str) -> Option<&mut V4Format> {
+        let style = self.get_style_mut(old)?;
+        Some(style.set_name(new))
+    }
+
+    /// get the column names of the `Format: ` line in the order they are written.
+    pub fn get_format(&self) -> Vec<String> {
+        self.format.clone()
+    }
+
+    /// set the column names of the `Format: ` line. Styles are written with their values in this
+    /// order.
+    pub fn set_format(&mut self, columns: &[&str]) -> &mut Self {
+        self.format = format_columns(columns);
+        self
+    }
+
+    /// The number of styles in the section.
+    pub fn len(&self) -> usize {
+        self.styles.len()
+    }
+
+    /// Returns `true` if the section has no styles.
+    pub fn is_empty(&self) -> bool {
+        self.styles.is_empty()
+    }
+
+    /// Iterate over the styles in the order they appear in the file.
+    pub fn iter(&self) -> std::slice::Iter<'_, V4Format> {
+        self.styles.iter()
+    }
+
+    /// Iterate mutably over the styles in the order they appear in the file.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, V4Format> {
+        self.styles.iter_mut()
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.styles.iter().position(|style| style.name.as_deref() == Some(name))
+    }
+}
+
+impl Default for Styles {
+    /// Styles holding only the '`Default`' style.
+    fn default() -> Styles {
+        Styles {
+            format: format_columns(&V4_FORMAT),
+            styles: vec![V4Format::default()],
+            raw_lines: Vec::new(),
+            verbatim: Vec::new(),
+        }
+    }
+}
+
 
 /// # Events
 /// In `Advanced SubStation Alpha` Events is the core part of the subtitle file.
@@ -806,7 +1396,15 @@ impl V4Format {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Events {
+    format: Vec<String>,
     pub dialogues: Dialogues,
+    /// comments and other lines of the section which are not dialogues, along with the number
+    /// of `Format: ` and `Dialogue: ` lines written before them.
+    raw_lines: Vec<(usize, String)>,
+    /// the `Format: ` and event lines which were not read the way they are written, like
+    /// `Dialogue:0,...` without a space, with the line they are written as. They are written
+    /// back as they were read as long as they are not modified.
+    verbatim: Vec<(String, String)>,
 }
 
 impl Events {
@@ -832,6 +1430,26 @@ impl Events {
     pub fn get_dialogues(&self) -> Vec<Dialogue> {
         return self.dialogues.dialogues.clone();
     }
+
+    /// Returns the plain text of every `Dialogue`, in the same order as `get_dialogues`.
+    /// A `Dialogue` without text gives an empty string.
+    pub fn get_plain_texts(&self) -> Vec<String> {
+        self.dialogues.dialogues.iter()
+            .map(|dialogue| dialogue.get_plain_text().unwrap_or_default())
+            .collect()
+    }
+
+    /// get the column names of the `Format: ` line in the order they are written.
+    pub fn get_format(&self) -> Vec<String> {
+        self.format.clone()
+    }
+
+    /// set the column names of the `Format: ` line. Dialogues are written with their values in
+    /// this order, `Text` should always be the last one.
+    pub fn set_format(&mut self, columns: &[&str]) -> &mut Self {
+        self.format = format_columns(columns);
+        self
+    }
 }
 
 impl Events {
@@ -840,13 +1458,16 @@ impl Events {
     pub fn new() -> Events {
         let dialogue = Dialogue::new();
         Events {
+            format: format_columns(&EVENT_FORMAT),
             dialogues: 
                 Dialogues {
                     dialogues: 
                         vec![
                             dialogue
                         ]
-                }
+                },
+            raw_lines: Vec::new(),
+            verbatim: Vec::new(),
         }
     }
 
@@ -943,14 +1564,18 @@ impl Events {
 impl Default for Events {
     fn default() -> Events {
         Events {
+            format: format_columns(&EVENT_FORMAT),
             dialogues: 
                 Dialogues {
                         dialogues: vec![
                         Dialogue {
+                            kind: EventKind::Dialogue,
                             event: EventFormat::default(),
                         }
                     ]
-                }
+                },
+            raw_lines: Vec::new(),
+            verbatim: Vec::new(),
         }
     }
 }
@@ -961,6 +1586,190 @@ impl Events {
         *self = events;
         self
     }
+
+    /// Move every event `millis` milliseconds later, or earlier when it is negative.
+    /// See `Dialogue::shift`.
+    pub fn shift(&mut self, millis: i64, adjust_tags: bool) -> &mut Events {
+        self.shift_where(millis, adjust_tags, |_| true)
+    }
+
+    /// Write the dialogues in the SubRip (`.srt`) format, ordered by their start time.
+    /// Comments and the other kinds of events are left out, and so are the dialogues without
+    /// any text to display. `\b`, `\i`, `\u` and `\c` become HTML tags and the other override
+    /// tags, like positioning, are dropped. See `TaggedText::to_html`.
+    pub fn to_srt(&self) -> String {
+        let mut captions: Vec<parser::SrtData> = self.dialogues.dialogues.iter()
+            .filter(|dialogue| dialogue.get_kind() == EventKind::Dialogue)
+            .filter_map(|dialogue| {
+                let text = dialogue.get_tagged_text()?.to_html();
+                if text.trim().is_empty() {
+                    return None;
+                }
+                Some(parser::SrtData {
+                    index: String::new(),
+                    start: dialogue.get_start()?,
+                    end: dialogue.get_end()?,
+                    text,
+                })
+            })
+            .collect();
+        captions.sort_by_key(|caption| caption.start);
+        for (i, caption) in captions.iter_mut().enumerate() {
+            caption.index = (i + 1).to_string();
+        }
+
+        parser::SrtData::new().write_srt(&captions)
+    }
+
+    /// Convert the dialogues to MicroDVD, ordered by their start time and timed in the frames of
+    /// `timecodes`. Comments, the other kinds of events and the dialogues without any text to
+    /// display are left out. Only the bold, italic, underline and colour of each line of text are
+    /// kept, see `MicroDvd`.
+    ///
+    /// ```rust
+    /// use ass_parser::{Dialogue, Events, Timecodes, Timestamp};
+    ///
+    /// let mut events = Events::default();
+    /// events.dialogues.dialogues = vec![Dialogue::default()
+    ///     .set_start(Timestamp::new(0, 0, 1, 0))
+    ///     .set_end(Timestamp::new(0, 0, 3, 0))
+    ///     .set_text("{\\i1}Hello\\Nthere")];
+    ///
+    /// let sub = events.to_microdvd(&Timecodes::from_fps(25.0).unwrap());
+    /// assert_eq!("{25}{74}{Y:i}Hello|there\n", sub.to_string());
+    /// ```
+    pub fn to_microdvd(&self, timecodes: &Timecodes) -> MicroDvd {
+        let mut dialogues: Vec<&Dialogue> = self.dialogues.dialogues.iter()
+            .filter(|dialogue| dialogue.get_kind() == EventKind::Dialogue)
+            .collect();
+        dialogues.sort_by_key(|dialogue| dialogue.get_start());
+
+        let lines = dialogues.into_iter().filter_map(|dialogue| {
+            let text = dialogue.get_tagged_text()?;
+            if text.plain_text().trim().is_empty() {
+                return None;
+            }
+            Some(MicroDvdLine {
+                start: timecodes.start_frame(dialogue.get_start()?),
+                end: timecodes.end_frame(dialogue.get_end()?),
+                text: microdvd::from_tagged_text(&text),
+            })
+        });
+        MicroDvd {
+            fps: None,
+            lines: lines.collect(),
+        }
+    }
+
+    /// Convert the dialogues to enhanced LRC lyrics, ordered by their start time. The syllables
+    /// of karaoke lines start with their `<mm:ss.xx>` time, see `Tag::Karaoke`, and the lines of
+    /// text of a dialogue are joined with spaces. An empty line marks the end of a dialogue which
+    /// is not followed by another one straight away. Comments, the other kinds of events and the
+    /// dialogues without any text to display are left out.
+    ///
+    /// ```rust
+    /// use ass_parser::{Dialogue, Events, Timestamp};
+    ///
+    /// let mut events = Events::default();
+    /// events.dialogues.dialogues = vec![Dialogue::default()
+    ///     .set_start(Timestamp::new(0, 0, 12, 0))
+    ///     .set_end(Timestamp::new(0, 0, 14, 0))
+    ///     .set_text("{\\k50}Hello {\\kf70}world")];
+    ///
+    /// let lrc = events.to_lrc();
+    /// assert_eq!("[00:12.00]<00:12.00>Hello <00:12.50>world<00:13.20>\n[00:14.00]\n", lrc.to_string());
+    /// ```
+    pub fn to_lrc(&self) -> Lrc {
+        let mut dialogues: Vec<&Dialogue> = self.dialogues.dialogues.iter()
+            .filter(|dialogue| dialogue.get_kind() == EventKind::Dialogue)
+            .filter(|dialogue| dialogue.get_tagged_text().is_some_and(|text| !text.plain_text().trim().is_empty()))
+            .collect();
+        dialogues.sort_by_key(|dialogue| dialogue.get_start());
+
+        let mut lines = Vec::new();
+        for (i, dialogue) in dialogues.iter().enumerate() {
+            let (Some(text), Some(start), Some(end)) = (dialogue.get_tagged_text(), dialogue.get_start(), dialogue.get_end()) else {
+                continue;
+            };
+            lines.push(LrcLine {
+                start,
+                text: lrc::from_tagged_text(&text, start),
+            });
+            let next = dialogues.get(i + 1).and_then(|next| next.get_start());
+            let followed = next.is_some_and(|next| next <= end);
+            if !followed {
+                lines.push(LrcLine {
+                    start: end,
+                    text: String::new(),
+                });
+            }
+        }
+        Lrc {
+            metadata: Vec::new(),
+            lines,
+        }
+    }
+
+    /// Convert the timing of every event from a video at `from` frames per second to the same
+    /// video at `to` frames per second, eg. from a 25 fps PAL release to 23.976 fps.
+    /// See `Dialogue::scale_times`. Returns `None`, leaving the events as they are, if a
+    /// framerate is not a finite number above zero.
+    pub fn change_framerate(&mut self, from: f64, to: f64, scale_tags: bool) -> Option<&mut Events> {
+        if ![from, to].iter().all(|fps| fps.is_finite() && *fps > 0.0) {
+            return None;
+        }
+        let ratio = from / to;
+        for dialogue in self.dialogues.dialogues.iter_mut() {
+            *dialogue = dialogue.clone().scale_times(ratio, scale_tags);
+        }
+        Some(self)
+    }
+
+    /// Move the start and end of every event to the nearest keyframe at most `threshold` frames
+    /// away. A line snapped at its end stops right before the keyframe. A snap which would make
+    /// a line end at or before its start is skipped, the one of the end first.
+    pub fn snap_to_keyframes(&mut self, keyframes: &Keyframes, timecodes: &Timecodes, threshold: usize) -> &mut Events {
+        let snap = |frame| keyframes.nearest(frame, threshold).map(|keyframe| timecodes.start_time(keyframe));
+        for dialogue in self.dialogues.dialogues.iter_mut() {
+            let (Some(start), Some(end)) = (dialogue.get_start(), dialogue.get_end()) else {
+                continue;
+            };
+            let snapped_start = snap(timecodes.start_frame(start)).unwrap_or(start);
+            let snapped_end = snap(timecodes.end_frame(end) + 1).unwrap_or(end);
+            let candidates = [(snapped_start, snapped_end), (snapped_start, end), (start, snapped_end)];
+            if let Some((start, end)) = candidates.into_iter().find(|(start, end)| start < end) {
+                *dialogue = dialogue.clone().set_start(start).set_end(end);
+            }
+        }
+        self
+    }
+
+    /// Move the events for which `filter` returns true `millis` milliseconds later, or earlier
+    /// when it is negative. See `Dialogue::shift`.
+    /// ```rust
+    /// use ass_parser::{Events, Dialogue, Timestamp};
+    ///
+    /// let mut events = Events::default();
+    /// events.add_dialogue(Dialogue::default()
+    ///     .set_style("Signs")
+    ///     .set_start(Timestamp::new(0, 0, 1, 0))
+    ///     .set_end(Timestamp::new(0, 0, 2, 0)));
+    ///
+    /// events.shift_where(-1500, false, |dialogue| dialogue.get_style().as_deref() == Some("Signs"));
+    /// assert_eq!(Some(Timestamp::ZERO), events.get_dialogues()[1].get_start());
+    /// assert_eq!(Some(Timestamp::new(0, 0, 0, 50)), events.get_dialogues()[1].get_end());
+    /// ```
+    pub fn shift_where<F>(&mut self, millis: i64, adjust_tags: bool, mut filter: F) -> &mut Events
+    where
+        F: FnMut(&Dialogue) -> bool,
+    {
+        for dialogue in self.dialogues.dialogues.iter_mut() {
+            if filter(dialogue) {
+                *dialogue = dialogue.clone().shift(millis, adjust_tags);
+            }
+        }
+        self
+    }
 }
 
 /// # Dialogues
@@ -974,13 +1783,60 @@ pub struct Dialogues {
 /// `Dialogue`.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Dialogue {
+    kind: EventKind,
     event: EventFormat
 }
 
-#[derive(Debug, PartialEq,Clone)]
-struct EventFormat {
-    layer: Option<String>,
-    start: Option<String>,
+/// The type of an event line, which is the word it starts with in the `[Events]` section.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EventKind {
+    /// `Dialogue: ` a subtitle which is displayed onscreen.
+    Dialogue,
+    /// `Comment: ` a line which is not displayed. Often used to keep the original text of a
+    /// translated line.
+    Comment,
+    /// `Picture: ` an SSA event displaying the image file named in its `Text`.
+    Picture,
+    /// `Sound: ` an SSA event playing the sound file named in its `Text`.
+    Sound,
+    /// `Movie: ` an SSA event playing the video file named in its `Text`.
+    Movie,
+    /// `Command: ` an SSA event running the program named in its `Text`.
+    Command,
+}
+
+impl EventKind {
+    const ALL: [EventKind; 6] = [
+        EventKind::Dialogue,
+        EventKind::Comment,
+        EventKind::Picture,
+        EventKind::Sound,
+        EventKind::Movie,
+        EventKind::Command,
+    ];
+
+    fn head(&self) -> &'static str {
+        match self {
+            EventKind::Dialogue => EVENT_HEAD,
+            EventKind::Comment => COMMENT_HEAD,
+            EventKind::Picture => PICTURE_HEAD,
+            EventKind::Sound => SOUND_HEAD,
+            EventKind::Movie => MOVIE_HEAD,
+            EventKind::Command => COMMAND_HEAD,
+        }
+    }
+
+    /// Find the kind of an event line and return it with the values of the line. The space
+    /// after the `:` can be left out.
+    fn strip_head(line: &str) -> Option<(EventKind, &str)> {
+        Self::ALL.iter().find_map(|kind| Some((*kind, strip_head(line, kind.head())?)))
+    }
+}
+
+#[derive(Debug, PartialEq,Clone)]
+struct EventFormat {
+    layer: Option<String>,
+    start: Option<String>,
     end: Option<String>,
     style: Option<String>,
     name: Option<String>,
@@ -989,7 +1845,9 @@ struct EventFormat {
     marginv: Option<String>,
     effect: Option<String>,
     text: Option<String>,
-    color: Option<String>
+    color: Option<AssColor>,
+    /// columns of the `Format: ` line which are not part of the `[Events]` format.
+    extra: Vec<(String, String)>,
 }
 
 impl Default for EventFormat {
@@ -1006,6 +1864,7 @@ impl Default for EventFormat {
             effect: Some("".to_string()),
             text: None,
             color: None,
+            extra: Vec::new(),
         }
     }
 }
@@ -1013,6 +1872,7 @@ impl Default for EventFormat {
 impl Dialogue {
     pub fn new() -> Self {
         Self {
+            kind: EventKind::Dialogue,
             event: EventFormat {
                 layer: None,
                 start: None,
@@ -1025,6 +1885,7 @@ impl Dialogue {
                 effect: None,
                 text: None,
                 color: None,
+                extra: Vec::new(),
             }
         }
     }
@@ -1033,31 +1894,145 @@ impl Dialogue {
 impl Default for Dialogue {
     fn default() -> Dialogue {
         Dialogue {
+            kind: EventKind::Dialogue,
             event: EventFormat::default()
         }
     }
 }
 
+impl EventFormat {
+    fn get_array(&self) -> [&Option<String>; 10] {
+        [
+            &self.layer,
+            &self.start,
+            &self.end,
+            &self.style,
+            &self.name,
+            &self.marginl,
+            &self.marginr,
+            &self.marginv,
+            &self.effect,
+            &self.text,
+        ]
+    }
+
+    fn get_array_mut(&mut self) -> [&mut Option<String>; 10] {
+        [
+            &mut self.layer,
+            &mut self.start,
+            &mut self.end,
+            &mut self.style,
+            &mut self.name,
+            &mut self.marginl,
+            &mut self.marginr,
+            &mut self.marginv,
+            &mut self.effect,
+            &mut self.text,
+        ]
+    }
+
+    fn column_index(column: &str) -> Option<usize> {
+        EVENT_FORMAT.iter().position(|name| name.eq_ignore_ascii_case(column.trim()))
+    }
+}
+
 impl Dialogue {
-    fn to_string(&self) -> String {
-        let mut dialogue_string = String::new();
-        dialogue_string.push_str(EVENT_HEAD);
-        dialogue_string.push_str(&(self.event.layer.as_ref().unwrap_or(&"".to_owned()).to_owned() + ","));
-        dialogue_string.push_str(&(self.event.start.as_ref().unwrap_or(&"".to_owned()).to_owned() + ","));
-        dialogue_string.push_str(&(self.event.end.as_ref().unwrap_or(&"".to_owned()).to_owned() + ","));
-        dialogue_string.push_str(&(self.event.style.as_ref().unwrap_or(&"".to_owned()).to_owned() + ","));
-        dialogue_string.push_str(&(self.event.name.as_ref().unwrap_or(&"".to_owned()).to_owned() + ","));
-        dialogue_string.push_str(&(self.event.marginl.as_ref().unwrap_or(&"".to_owned()).to_owned() + ","));
-        dialogue_string.push_str(&(self.event.marginr.as_ref().unwrap_or(&"".to_owned()).to_owned() + ","));
-        dialogue_string.push_str(&(self.event.marginv.as_ref().unwrap_or(&"".to_owned()).to_owned() + ","));
-        dialogue_string.push_str(&(self.event.effect.as_ref().unwrap_or(&"".to_owned()).to_owned() + ","));
-        dialogue_string.push_str(&(self.event.text.as_ref().unwrap_or(&"".to_owned()).to_owned() + "\n"));
+    fn to_line(&self, format: &[String]) -> String {
+        let values: Vec<String> = format.iter()
+            .map(|column| self.get_field(column).unwrap_or_default())
+            .collect();
+
+        self.kind.head().to_owned() + &values.join(",")
+    }
+
+    /// get the raw value of a column by the name used in the `Format: ` line.
+    /// Column names are matched case insensitively. Columns which are not part of the `[Events]`
+    /// format (eg. `Marked` of SSA v4 files) are kept as they were read.
+    pub fn get_field(&self, column: &str) -> Option<String> {
+        match EventFormat::column_index(column) {
+            Some(i) => self.event.get_array()[i].clone(),
+            None => self.event.extra.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(column.trim()))
+                .map(|(_, value)| value.clone()),
+        }
+    }
 
-        return dialogue_string;
+    /// set the raw value of a column by the name used in the `Format: ` line.
+    pub fn set_field(mut self, column: &str, value: &str) -> Self {
+        match EventFormat::column_index(column) {
+            Some(i) => *self.event.get_array_mut()[i] = Some(value.to_string()),
+            None => match self.event.extra.iter_mut().find(|(name, _)| name.eq_ignore_ascii_case(column.trim())) {
+                Some(extra) => extra.1 = value.to_string(),
+                None => self.event.extra.push((column.trim().to_string(), value.to_string())),
+            },
+        }
+        self
     }
 }
 
 impl Dialogue {
+    /// Move the start and end times `millis` milliseconds later, or earlier when it is negative.
+    /// Times which would be before the start of the video are set to `0:00:00.00`.
+    ///
+    /// When the start is cut that way and `adjust_tags` is true, the times of `\move`, `\t`,
+    /// `\fad` and `\fade`, which are relative to the start of the line, are changed so the
+    /// animations stay in sync with the video, and the `\k` syllables are shortened by the time
+    /// which was cut.
+    pub fn shift(self, millis: i64, adjust_tags: bool) -> Self {
+        let shifted = |time: Timestamp| time.as_millis() as i64 + millis;
+        let (start, end) = (self.get_start(), self.get_end());
+        let mut dialogue = self;
+
+        if let Some(start) = start {
+            let new_start = shifted(start).max(0);
+            dialogue = dialogue.set_start(Timestamp::from_millis(new_start as u64));
+
+            let delay = (new_start - shifted(start)) as i32;
+            let duration = end.map_or(0, |end| (end - start).as_millis() as i32);
+            if adjust_tags && delay > 0 {
+                if let Some(mut text) = dialogue.get_tagged_text() {
+                    if text.delay_start(delay, duration) {
+                        dialogue = dialogue.set_tagged_text(&text);
+                    }
+                }
+            }
+        }
+        if let Some(end) = end {
+            dialogue = dialogue.set_end(Timestamp::from_millis(shifted(end).max(0) as u64));
+        }
+        dialogue
+    }
+
+    /// Multiply the start and end times by `ratio`, rounded to the nearest centisecond.
+    ///
+    /// When `scale_tags` is true, the durations of `\k` and the times of `\t`, `\move`, `\fad`
+    /// and `\fade` are multiplied as well.
+    pub fn scale_times(self, ratio: f64, scale_tags: bool) -> Self {
+        let (start, end) = (self.get_start(), self.get_end());
+        let mut dialogue = self;
+
+        if let Some(start) = start {
+            dialogue = dialogue.set_start(start.scale(ratio));
+        }
+        if let Some(end) = end {
+            dialogue = dialogue.set_end(end.scale(ratio));
+        }
+        if scale_tags {
+            if let Some(mut text) = dialogue.get_tagged_text() {
+                if text.scale_times(ratio) {
+                    dialogue = dialogue.set_tagged_text(&text);
+                }
+            }
+        }
+        dialogue
+    }
+
+    /// set the kind of the event. A `Dialogue` is displayed, a `Comment` is kept in the file but
+    /// not displayed.
+    pub fn set_kind(mut self, kind: EventKind) -> Self {
+        self.kind = kind;
+        self
+    }
     /// set the layer
     /// Layer (any integer)
     /// Subtitles having different layer number will be ignored during the collusion detection.
@@ -1067,14 +2042,14 @@ impl Dialogue {
 		self
 	}
     /// set the start time of the subtitle.
-    /// Start Time of the Event, in 0:00:00:00 format ie. Hrs:Mins:Secs:hundredths. This is the time elapsed during script playback at which the text will appear onscreen. Note that there is a single digit for the hours!
-    pub fn set_start(mut self, value: &str) -> Self {
+    /// Start Time of the Event, written in 0:00:00.00 format ie. Hrs:Mins:Secs.hundredths. This is the time elapsed during script playback at which the text will appear onscreen. Note that there is a single digit for the hours!
+    pub fn set_start(mut self, value: Timestamp) -> Self {
 		self.event.start = Some(value.to_string());
 		self
     }
 	/// set the end time of the subtitle.
-    ///  End Time of the Event, in 0:00:00:00 format ie. Hrs:Mins:Secs:hundredths. This is the time elapsed during script playback at which the text will disappear offscreen. Note that there is a single digit for the hours!
-    pub fn set_end(mut self, value: &str) -> Self {
+    ///  End Time of the Event, written in 0:00:00.00 format ie. Hrs:Mins:Secs.hundredths. This is the time elapsed during script playback at which the text will disappear offscreen. Note that there is a single digit for the hours!
+    pub fn set_end(mut self, value: Timestamp) -> Self {
 		self.event.end = Some(value.to_string());
 		self
 	}
@@ -1127,39 +2102,80 @@ impl Dialogue {
 	}
 
     /// set the color of the subtitle.
-    pub fn set_colour(mut self, color: HexColor) -> Self {
-        let colour = AssFileOptions::get_ass_color_text(color);
-        match &self.event.text {
-            Some(text) => {
-                let new_text = &(colour.clone() + text);
-                // If you know a better way, please do create a pull request
-                self = self.clone().set_text(new_text);
-                self.event.color = Some(colour);
-                self
+    /// Accepts an `AssColor` or a `HexColor`, a transparent colour also sets the alpha of the text.
+    /// The colour replaces the one of the override block at the start of the text, if there is
+    /// one.
+    pub fn set_colour(mut self, color: impl Into<AssColor>) -> Self {
+        let color = color.into();
+        let mut colour = vec![Tag::Colour(1, color)];
+        if color.alpha != 0 {
+            colour.push(Tag::ColourAlpha(1, color.alpha));
+        }
+        self.event.color = Some(color);
+
+        let mut text = self.get_tagged_text().unwrap_or_default();
+        match text.segments.first_mut() {
+            Some(Segment::Override(tags)) => {
+                tags.retain(|tag| !matches!(tag, Tag::Colour(1, _) | Tag::ColourAlpha(1, _)));
+                tags.splice(0..0, colour);
+            },
+            _ => text.segments.insert(0, Segment::Override(colour)),
+        }
+        self.set_tagged_text(&text)
+    }
+
+    /// set the text from plain text and override tags.
+    pub fn set_tagged_text(self, text: &TaggedText) -> Self {
+        self.set_text(&text.to_string())
+    }
+
+    /// set the drawing of the subtitle, replacing the text after the `\p` tag.
+    /// When the text has no drawing, `{\p1}` and the drawing are added at its end.
+    pub fn set_drawing(self, drawing: &Drawing) -> Self {
+        let mut text = self.get_tagged_text().unwrap_or_default();
+        let drawings = text.drawing_segments();
+
+        match drawings.first() {
+            Some(&first) => {
+                text.segments[first] = Segment::Text(drawing.to_string());
+                for &i in drawings[1..].iter().rev() {
+                    text.segments.remove(i);
+                }
             },
             None => {
-                self.event.color = Some(colour.clone());
-                self.set_text(&colour)
-            }
+                text.segments.push(Segment::Override(vec![Tag::Drawing(1)]));
+                text.segments.push(Segment::Text(drawing.to_string()));
+            },
         }
+        self.set_tagged_text(&text)
     }
 }
 
 
 impl Dialogue {
+    /// get the kind of the event.
+    pub fn get_kind(&self) -> EventKind {
+        self.kind
+    }
+    /// Returns `true` if this event is a `Comment: ` line which is not displayed.
+    pub fn is_comment(&self) -> bool {
+        self.kind == EventKind::Comment
+    }
     /// get the layer of the subtitle
     pub fn get_layer(&self) -> Option<String> {
 				return self.event.layer.clone();
 	}
     /// get the start time of the `Dialogue`
-    /// Start Time of the Event, in 0:00:00:00 format ie. Hrs:Mins:Secs:hundredths. This is the time elapsed during script playback at which the text will appear onscreen. Note that there is a single digit for the hours!
-    pub fn get_start(&self) -> Option<String> {
-				return self.event.start.clone();
+    /// Start Time of the Event. This is the time elapsed during script playback at which the text will appear onscreen.
+    /// `None` if it is not set or not a valid timestamp, use `get_field("Start")` for the raw value.
+    pub fn get_start(&self) -> Option<Timestamp> {
+				return self.event.start.as_ref()?.parse().ok();
     }
 	/// get the end time of the `Dialogue`.
-    ///  End Time of the Event, in 0:00:00:00 format ie. Hrs:Mins:Secs:hundredths. This is the time elapsed during script playback at which the text will disappear offscreen. Note that there is a single digit for the hours!
-    pub fn get_end(&self) -> Option<String> {
-				return self.event.end.clone();
+    ///  End Time of the Event. This is the time elapsed during script playback at which the text will disappear offscreen.
+    /// `None` if it is not set or not a valid timestamp, use `get_field("End")` for the raw value.
+    pub fn get_end(&self) -> Option<Timestamp> {
+				return self.event.end.as_ref()?.parse().ok();
 	}
     /// get the style of the `Dialogue`.
     pub fn get_style(&self) -> Option<String> {
@@ -1200,23 +2216,175 @@ impl Dialogue {
 				return self.event.text.clone();
 	}
 
-    /// get the color of the subtitle.
-    pub fn get_colour(&self) -> Option<String> {
-        return self.event.color.clone()
+    /// get the text displayed on screen, without override tags and drawings.
+    /// `\N` and `\n` become line breaks and `\h` a non-breaking space.
+    pub fn get_plain_text(&self) -> Option<String> {
+        self.get_tagged_text().map(|text| text.plain_text())
+    }
+
+    /// get the drawing of the subtitle, the text after a `\p` tag.
+    /// `None` if there is no drawing or it is not valid.
+    pub fn get_drawing(&self) -> Option<Drawing> {
+        let text = self.get_tagged_text()?;
+        let commands: Vec<String> = text.drawing_segments().into_iter()
+            .filter_map(|i| match &text.segments[i] {
+                Segment::Text(commands) => Some(commands.clone()),
+                Segment::Override(_) => None,
+            })
+            .collect();
+        if commands.is_empty() {
+            return None;
+        }
+        commands.join(" ").parse().ok()
+    }
+
+    /// get the text split into plain text and override tags.
+    pub fn get_tagged_text(&self) -> Option<TaggedText> {
+        self.event.text.as_deref().map(TaggedText::parse)
+    }
+
+    /// get the color set with `set_colour`.
+    pub fn get_colour(&self) -> Option<AssColor> {
+        return self.event.color
     }
 }
 
 pub struct AssFileOptions{}
 
+/// Options of the conversions of other subtitle formats, like `AssFile::convert_srt`, the
+/// style, resolution and colours of the converted subtitles.
+///
+/// ```rust
+/// use ass_parser::{ConvertOptions, V4Format};
+/// use hex_color::HexColor;
+///
+/// let mut options = ConvertOptions::default();
+/// options.set_playres(1920, 1080)
+///     .set_primarycolour(HexColor::YELLOW);
+/// options.style_mut().set_fontsize(64.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvertOptions {
+    style: V4Format,
+    playresx: u32,
+    playresy: u32,
+}
+
+impl Default for ConvertOptions {
+    /// The '`Default`' style, on a 384x288 screen.
+    fn default() -> ConvertOptions {
+        ConvertOptions {
+            style: V4Format::default(),
+            playresx: 384,
+            playresy: 288,
+        }
+    }
+}
+
+impl ConvertOptions {
+    /// set the style of every dialogue. Its font size and margins are relative to the PlayRes.
+    pub fn set_style(&mut self, style: V4Format) -> &mut Self {
+        self.style = style;
+        self
+    }
+    /// get the style to change some of its fields.
+    pub fn style_mut(&mut self) -> &mut V4Format {
+        &mut self.style
+    }
+    /// set the PlayResX and PlayResY of the converted file.
+    pub fn set_playres(&mut self, x: u32, y: u32) -> &mut Self {
+        self.playresx = x;
+        self.playresy = y;
+        self
+    }
+    /// set the colour of the text.
+    pub fn set_primarycolour(&mut self, value: impl Into<AssColor>) -> &mut Self {
+        self.style.set_primarycolour(value);
+        self
+    }
+    /// set the colour of the outline of the text.
+    pub fn set_outlinecolour(&mut self, value: impl Into<AssColor>) -> &mut Self {
+        self.style.set_outlinecolour(value);
+        self
+    }
+    /// set the colour of the shadow, or of the box with `BorderStyle::OpaqueBox`.
+    pub fn set_backcolour(&mut self, value: impl Into<AssColor>) -> &mut Self {
+        self.style.set_backcolour(value);
+        self
+    }
+}
+
 /// `script`, `v4` and `event` are fields in `Components`
 #[derive(Clone, PartialEq, Debug)]
 pub struct Components {
     /// instance holding the scirpt field.
     pub script: ScriptInfo,
-    /// instance holding the V4 field of.
-    pub v4: V4Format,
+    /// instance holding every style of the V4 field.
+    pub v4: Styles,
     /// instance holding the Events field of.
     pub events: Events,
+    /// every section the parser does not know about, eg. `[Fonts]`, `[Graphics]` or
+    /// `[Aegisub Project Garbage]`.
+    pub sections: Vec<Section>,
+    layout: Layout,
+}
+
+/// A section of an `Advanced SubStation Alpha` file which is not parsed, its lines are kept as
+/// they are and written back unchanged.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Section {
+    /// The name of the section, without the square brackets.
+    pub name: String,
+    /// Every line of the section.
+    pub lines: Vec<String>,
+}
+
+impl Section {
+    pub fn new(name: &str) -> Section {
+        Section {
+            name: name.to_string(),
+            lines: Vec::new(),
+        }
+    }
+}
+
+/// Everything needed to write a file back exactly the way it was read.
+#[derive(Clone, PartialEq, Debug)]
+struct Layout {
+    bom: bool,
+    line_ending: String,
+    trailing_newline: bool,
+    /// lines before the first section header.
+    preamble: Vec<String>,
+    /// the sections in the order they appear, with the number of blank lines following each.
+    order: Vec<(SectionId, usize)>,
+    /// `[V4+ Styles]`, or `[V4 Styles]` for SSA v4 files.
+    styles_header: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum SectionId {
+    Script,
+    Styles,
+    Events,
+    Other(String),
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout {
+            bom: false,
+            line_ending: "\n".to_string(),
+            trailing_newline: true,
+            preamble: Vec::new(),
+            order: vec![
+                (SectionId::Script, 1),
+                (SectionId::Styles, 1),
+                (SectionId::Events, 0),
+            ],
+            styles_header: V4_HEADER.to_string(),
+        }
+    }
 }
 
 
@@ -1258,17 +2426,358 @@ impl AssFile {
             _ass_file: String::new(),
             components: Components {
                 script: ScriptInfo::new(),
-                v4: V4Format::new(),
+                v4: Styles::new(),
                 events: Events::new(),
+                sections: Vec::new(),
+                layout: Layout::default(),
+            }
+        }
+    }
+
+    /// Convert a SubRip file to a complete `AssFile`, ready to be saved.
+    /// Every caption becomes a `Dialogue` using the style of `options`.
+    pub fn convert_srt(filename: &str, options: &ConvertOptions) -> std::result::Result<AssFile, ParseError> {
+        let contents = get_contents(filename)?;
+        let mut ass_file = AssFile::convert_srt_contents(&contents, options)?;
+        ass_file._ass_file = filename.to_string();
+        Ok(ass_file)
+    }
+
+    /// Convert the content of a SubRip file to a complete `AssFile`. See `AssFile::convert_srt`.
+    ///
+    /// ```rust
+    /// use ass_parser::{AssFile, ConvertOptions};
+    ///
+    /// let srt = "1\n00:00:01,500 --> 00:00:04,900\n<i>Hello</i>\nthere\n";
+    /// let ass_file = AssFile::convert_srt_contents(srt, &ConvertOptions::default()).unwrap();
+    ///
+    /// let dialogue = &ass_file.events.get_dialogues()[0];
+    /// assert_eq!(Some("{\\i1}Hello{\\i0}\\Nthere".to_string()), dialogue.get_text());
+    /// assert_eq!(Some("Default".to_string()), dialogue.get_style());
+    /// ```
+    pub fn convert_srt_contents(contents: &str, options: &ConvertOptions) -> std::result::Result<AssFile, ParseError> {
+        let captions = parser::SrtData::new().parse_srt(contents)?;
+        let style_name = options.style.get_name().unwrap_or_default();
+
+        let dialogues = captions.into_iter()
+            .map(|caption| Dialogue::default()
+                .set_start(caption.start)
+                .set_end(caption.end)
+                .set_style(&style_name)
+                .set_text(&caption.text))
+            .collect();
+        Ok(AssFile::with_dialogues(options, dialogues))
+    }
+
+    /// Convert a WebVTT file to a complete `AssFile`, ready to be saved.
+    /// Every cue becomes a `Dialogue` using the style of `options`. The settings of a cue give
+    /// the alignment and margins of its `Dialogue`, relative to the PlayRes of `options`, and
+    /// its `<v>` tag gives the name. `NOTE` blocks become `Comment: ` events while `STYLE` and
+    /// `REGION` blocks are dropped. See `CueSettings::alignment` and `VttCue::ass_text`.
+    pub fn convert_vtt(filename: &str, options: &ConvertOptions) -> std::result::Result<AssFile, ParseError> {
+        let contents = get_contents(filename)?;
+        let mut ass_file = AssFile::convert_vtt_contents(&contents, options)?;
+        ass_file._ass_file = filename.to_string();
+        Ok(ass_file)
+    }
+
+    /// Convert the content of a WebVTT file to a complete `AssFile`. See `AssFile::convert_vtt`.
+    ///
+    /// ```rust
+    /// use ass_parser::{AssFile, ConvertOptions};
+    ///
+    /// let vtt = "WEBVTT\n\n00:01.500 --> 00:04.900 line:0\n<v Tom><b>Hello</b>\nthere\n";
+    /// let ass_file = AssFile::convert_vtt_contents(vtt, &ConvertOptions::default()).unwrap();
+    ///
+    /// let dialogue = &ass_file.events.get_dialogues()[0];
+    /// assert_eq!(Some("{\\an8\\b1}Hello{\\b0}\\Nthere".to_string()), dialogue.get_text());
+    /// assert_eq!(Some("Tom".to_string()), dialogue.get_name());
+    /// ```
+    pub fn convert_vtt_contents(contents: &str, options: &ConvertOptions) -> std::result::Result<AssFile, ParseError> {
+        let vtt = WebVtt::parse(contents)?;
+        let style_name = options.style.get_name().unwrap_or_default();
+        let style_alignment = options.style.get_alignment().unwrap_or_default();
+        let mut dialogues = Vec::new();
+        let mut last_end = Timestamp::ZERO;
+
+        for block in vtt.blocks {
+            let dialogue = Dialogue::default().set_style(&style_name);
+            match block {
+                VttBlock::Cue(cue) => {
+                    let mut text = TaggedText::parse(&cue.ass_text());
+                    let alignment = cue.settings.alignment();
+                    if alignment != style_alignment {
+                        match text.segments.first_mut() {
+                            Some(Segment::Override(tags)) => tags.insert(0, Tag::Alignment(alignment)),
+                            _ => text.segments.insert(0, Segment::Override(vec![Tag::Alignment(alignment)])),
+                        }
+                    }
+                    let [left, right, vertical] = cue.settings.margins(options.playresx, options.playresy);
+                    last_end = cue.end;
+                    dialogues.push(dialogue
+                        .set_start(cue.start)
+                        .set_end(cue.end)
+                        .set_name(&cue.voice().unwrap_or_default())
+                        .set_marginl(&left.to_string())
+                        .set_marginr(&right.to_string())
+                        .set_marginv(&vertical.to_string())
+                        .set_tagged_text(&text));
+                },
+                VttBlock::Note(note) => {
+                    let mut text = String::new();
+                    parser::push_text(&mut text, &note);
+                    dialogues.push(dialogue
+                        .set_kind(EventKind::Comment)
+                        .set_start(last_end)
+                        .set_end(last_end)
+                        .set_text(&text));
+                },
+                VttBlock::Style(_) | VttBlock::Region(_) => {},
             }
         }
+        Ok(AssFile::with_dialogues(options, dialogues))
+    }
+
+    /// Convert a MicroDVD file to a complete `AssFile`, ready to be saved. The frames of each
+    /// subtitle are turned into times with `timecodes`, eg. `Timecodes::from_fps(23.976)`, and
+    /// every subtitle becomes a `Dialogue` using the style of `options`. The framerate some files
+    /// give on their first line can be read with `AssFile::from_microdvd`.
+    /// See `MicroDvdLine::ass_text`.
+    pub fn convert_microdvd(filename: &str, timecodes: &Timecodes, options: &ConvertOptions) -> std::result::Result<AssFile, ParseError> {
+        let contents = get_contents(filename)?;
+        let mut ass_file = AssFile::convert_microdvd_contents(&contents, timecodes, options)?;
+        ass_file._ass_file = filename.to_string();
+        Ok(ass_file)
+    }
+
+    /// Convert the content of a MicroDVD file to a complete `AssFile`.
+    /// See `AssFile::convert_microdvd`.
+    ///
+    /// ```rust
+    /// use ass_parser::{AssFile, ConvertOptions, Timecodes, Timestamp};
+    ///
+    /// let sub = "{25}{74}{y:i}Hello|there\n";
+    /// let ass_file = AssFile::convert_microdvd_contents(sub, &Timecodes::from_fps(25.0).unwrap(), &ConvertOptions::default()).unwrap();
+    ///
+    /// let dialogue = &ass_file.events.get_dialogues()[0];
+    /// assert_eq!(Some(Timestamp::new(0, 0, 0, 98)), dialogue.get_start());
+    /// assert_eq!(Some(Timestamp::new(0, 0, 2, 98)), dialogue.get_end());
+    /// assert_eq!(Some("{\\i1}Hello{\\i0}\\Nthere".to_string()), dialogue.get_text());
+    /// ```
+    pub fn convert_microdvd_contents(contents: &str, timecodes: &Timecodes, options: &ConvertOptions) -> std::result::Result<AssFile, ParseError> {
+        let sub = MicroDvd::parse(contents)?;
+        let style_name = options.style.get_name().unwrap_or_default();
+
+        let dialogues = sub.lines.iter()
+            .map(|line| Dialogue::default()
+                .set_start(timecodes.start_time(line.start))
+                .set_end(timecodes.end_time(line.end))
+                .set_style(&style_name)
+                .set_text(&line.ass_text()))
+            .collect();
+        Ok(AssFile::with_dialogues(options, dialogues))
+    }
+
+    /// Load Subtitles from a MicroDVD (`.sub`) file, timed in frames. See `MicroDvd::parse`.
+    pub fn from_microdvd(filename: &str) -> std::result::Result<MicroDvd, ParseError> {
+        MicroDvd::parse(&get_contents(filename)?)
+    }
+
+    /// Convert a TTML (or DFXP) file to a complete `AssFile`, ready to be saved.
+    /// Each `<style>` becomes a style based on the one of `options`, with its font, size, colour,
+    /// weight, slant, decorations, outline and background. Each `<p>` becomes a `Dialogue` whose
+    /// region gives its alignment and margins, relative to the PlayRes of `options`, and whose
+    /// `<span>` and `<br/>` elements become override tags and `\N`.
+    pub fn convert_ttml(filename: &str, options: &ConvertOptions) -> std::result::Result<AssFile, ParseError> {
+        let contents = get_contents(filename)?;
+        let mut ass_file = AssFile::convert_ttml_contents(&contents, options)?;
+        ass_file._ass_file = filename.to_string();
+        Ok(ass_file)
+    }
+
+    /// Convert the content of a TTML file to a complete `AssFile`. See `AssFile::convert_ttml`.
+    ///
+    /// ```rust
+    /// use ass_parser::{AssFile, ConvertOptions, Timestamp};
+    ///
+    /// let ttml = r#"<tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling">
+    ///   <head><styling><style xml:id="s1" tts:color="yellow"/></styling></head>
+    ///   <body><div>
+    ///     <p begin="1.5s" end="00:00:04.900" style="s1"><span tts:fontStyle="italic">Hello</span><br/>there</p>
+    ///   </div></body>
+    /// </tt>"#;
+    /// let ass_file = AssFile::convert_ttml_contents(ttml, &ConvertOptions::default()).unwrap();
+    ///
+    /// let dialogue = &ass_file.events.get_dialogues()[0];
+    /// assert_eq!(Some(Timestamp::new(0, 0, 1, 50)), dialogue.get_start());
+    /// assert_eq!(Some("{\\i1}Hello{\\i0}\\Nthere".to_string()), dialogue.get_text());
+    /// assert_eq!(Some("s1".to_string()), dialogue.get_style());
+    /// ```
+    pub fn convert_ttml_contents(contents: &str, options: &ConvertOptions) -> std::result::Result<AssFile, ParseError> {
+        let (styles, dialogues) = ttml::parse_ttml(contents, options)?;
+        let mut ass_file = AssFile::with_dialogues(options, dialogues);
+        for style in styles {
+            ass_file.components.v4.add_style(style);
+        }
+        Ok(ass_file)
+    }
+
+    /// Convert LRC lyrics to a complete `AssFile`, ready to be saved. Every line becomes a
+    /// `Dialogue` using the style of `options`, lasting until the next line, and the word times of
+    /// enhanced LRC become `\k` tags. The times are moved by the `offset` tag of the file.
+    /// See `Lrc::line_end` and `LrcLine::ass_text`.
+    pub fn convert_lrc(filename: &str, options: &ConvertOptions) -> std::result::Result<AssFile, ParseError> {
+        let contents = get_contents(filename)?;
+        let mut ass_file = AssFile::convert_lrc_contents(&contents, options)?;
+        ass_file._ass_file = filename.to_string();
+        Ok(ass_file)
+    }
+
+    /// Convert the content of an LRC file to a complete `AssFile`. See `AssFile::convert_lrc`.
+    ///
+    /// ```rust
+    /// use ass_parser::{AssFile, ConvertOptions, Timestamp};
+    ///
+    /// let lrc = "[00:12.00]<00:12.00>Hello <00:12.50>world\n[00:14.00]Again\n";
+    /// let ass_file = AssFile::convert_lrc_contents(lrc, &ConvertOptions::default()).unwrap();
+    ///
+    /// let dialogue = &ass_file.events.get_dialogues()[0];
+    /// assert_eq!(Some(Timestamp::new(0, 0, 14, 0)), dialogue.get_end());
+    /// assert_eq!(Some("{\\k50}Hello {\\k150}world".to_string()), dialogue.get_text());
+    /// ```
+    pub fn convert_lrc_contents(contents: &str, options: &ConvertOptions) -> std::result::Result<AssFile, ParseError> {
+        let lrc = Lrc::parse(contents)?;
+        let style_name = options.style.get_name().unwrap_or_default();
+
+        let dialogues = lrc.lines.iter().enumerate()
+            .filter(|(_, line)| !line.text.trim().is_empty())
+            .map(|(i, line)| {
+                let end = lrc.line_end(i);
+                Dialogue::default()
+                    .set_start(line.start)
+                    .set_end(end)
+                    .set_style(&style_name)
+                    .set_text(&line.ass_text(end))
+                    .shift(-lrc.offset(), true)
+            })
+            .collect();
+        Ok(AssFile::with_dialogues(options, dialogues))
+    }
+
+    /// Load lyrics from an LRC (`.lrc`) file. See `Lrc::parse`.
+    pub fn from_lrc(filename: &str) -> std::result::Result<Lrc, ParseError> {
+        Lrc::parse(&get_contents(filename)?)
+    }
+
+    /// A file holding the style and PlayRes of `options`, and `dialogues`.
+    fn with_dialogues(options: &ConvertOptions, dialogues: Vec<Dialogue>) -> AssFile {
+        let mut ass_file = AssFile::new();
+        ass_file.components.script
+            .set_script(ScriptInfo::default())
+            .set_playresx(options.playresx)
+            .set_playresy(options.playresy);
+        ass_file.components.v4.set_v4(options.style.clone());
+        ass_file.components.events.dialogues.dialogues = dialogues;
+        ass_file
+    }
+
+    /// Load Subtitles from a WebVTT file, keeping its cues, `NOTE`, `STYLE` and `REGION` blocks.
+    /// See `WebVtt::parse`.
+    pub fn from_vtt(filename: &str) -> std::result::Result<WebVtt, ParseError> {
+        WebVtt::parse(&get_contents(filename)?)
+    }
+
+    /// Convert the events to WebVTT, ordered by their start time. Each `Dialogue` becomes a cue
+    /// placed with its alignment and margin overrides, relative to the PlayRes of the script,
+    /// and its name is written as a `<v>` tag. `Comment: ` events become `NOTE` blocks, the
+    /// other kinds of events are left out. See `CueSettings::from_alignment`.
+    ///
+    /// ```rust
+    /// use ass_parser::{AssFile, ConvertOptions};
+    ///
+    /// let vtt = "WEBVTT\n\n00:01.500 --> 00:04.900 line:0%,start\n<i>Hello</i>\n";
+    /// let ass_file = AssFile::convert_vtt_contents(vtt, &ConvertOptions::default()).unwrap();
+    /// assert_eq!(vtt, ass_file.to_vtt().to_string().replace("00:00:", "00:"));
+    /// ```
+    pub fn to_vtt(&self) -> WebVtt {
+        let width = self.components.script.get_playresx().unwrap_or(384);
+        let height = self.components.script.get_playresy().unwrap_or(288);
+        let mut events: Vec<&Dialogue> = self.components.events.dialogues.dialogues.iter()
+            .filter(|dialogue| matches!(dialogue.get_kind(), EventKind::Dialogue | EventKind::Comment))
+            .collect();
+        events.sort_by_key(|dialogue| dialogue.get_start());
+
+        let blocks = events.into_iter().filter_map(|dialogue| {
+            let text = dialogue.get_tagged_text()?;
+            if dialogue.is_comment() {
+                let note = text.plain_text();
+                return (!note.trim().is_empty() && !note.contains("-->")).then_some(VttBlock::Note(note));
+            }
+            let mut cue_text = text.to_vtt();
+            if cue_text.trim().is_empty() {
+                return None;
+            }
+            if let Some(name) = dialogue.get_name().filter(|name| !name.is_empty()) {
+                cue_text = format!("<v {}>{}", vtt::escape(&name), cue_text);
+            }
+
+            let style = dialogue.get_style().and_then(|name| self.components.v4.get_style(&name));
+            let alignment = text.tags()
+                .find_map(|tag| match tag {
+                    Tag::Alignment(alignment) => Some(*alignment),
+                    _ => None,
+                })
+                .or_else(|| style?.get_alignment())
+                .unwrap_or_default();
+            let margin = |value: Option<String>| value.and_then(|value| value.trim().parse().ok()).unwrap_or(0);
+            let margins = [
+                margin(dialogue.get_marginl()),
+                margin(dialogue.get_marginr()),
+                margin(dialogue.get_marginv()),
+            ];
+
+            Some(VttBlock::Cue(VttCue {
+                identifier: String::new(),
+                start: dialogue.get_start()?,
+                end: dialogue.get_end()?,
+                settings: CueSettings::from_alignment(alignment, margins, width, height),
+                text: cue_text,
+            }))
+        });
+
+        WebVtt {
+            header: String::new(),
+            blocks: blocks.collect(),
+        }
+    }
+
+    /// Convert the styles and dialogues to a TTML document of the IMSC1 text profile, the
+    /// paragraphs ordered by their start time. Each style becomes a `<style>` and each group of
+    /// dialogues sharing an alignment and margins a `<region>`, relative to the PlayRes of the
+    /// script. The formatting of the text which differs from its style is written in `<span>`
+    /// elements, the other override tags and the drawings are left out, and so are comments.
+    ///
+    /// ```rust
+    /// use ass_parser::{AssFile, ConvertOptions};
+    ///
+    /// let srt = "1\n00:00:01,500 --> 00:00:04,900\n<i>Hello</i>\nthere\n";
+    /// let ass_file = AssFile::convert_srt_contents(srt, &ConvertOptions::default()).unwrap();
+    ///
+    /// let ttml = ass_file.to_ttml();
+    /// assert!(ttml.contains(r#"<style xml:id="Default" tts:fontFamily="Arial" tts:fontSize="16px""#));
+    /// assert!(ttml.contains(r#"<p begin="00:00:01.500" end="00:00:04.900" region="r1" style="Default"><span tts:fontStyle="italic">Hello</span><br/>there</p>"#));
+    /// ```
+    pub fn to_ttml(&self) -> String {
+        ttml::write_ttml(&self.components)
     }
 
     /// Load Subtitles from a SubRip file.
+    /// The lines of each caption are joined with `\N`. A `ParseError` is returned when the file
+    /// cannot be read or a timing line is not valid.
     ///
     /// # Example
     /// ``` rust
-    /// let srt_file = AssFile::from_srt("sample.srt");
+    /// let srt_file = AssFile::from_srt("sample.srt")?;
     ///
     /// for srt_seg in srt_file.iter() {
     ///    let start = &srt_seg.start;
@@ -1278,14 +2787,14 @@ impl AssFile {
     ///    println!("Start: {}\nEnd: {}\ntext: {}", start, end, text);
     /// 
     ///} ```
-    pub fn from_srt(filename: &str) -> Srt {
-        let file_contents = get_contents(filename).unwrap();
+    pub fn from_srt(filename: &str) -> std::result::Result<Srt, ParseError> {
+        let file_contents = get_contents(filename)?;
         let srtdata = parser::SrtData::new();
-        let srt = srtdata.parse_srt(file_contents);
+        let srt = srtdata.parse_srt(&file_contents)?;
 
-        Srt {
+        Ok(Srt {
             srt_data: srt,
-        }
+        })
     }
 }
 
@@ -1295,310 +2804,430 @@ impl Parser {
         Parser
     }
 
-    fn stringify_script(&self, scriptinfo: Vec<[&str; 2]>) -> String {
-        let mut contents = String::new();
+    fn combine_components(&self, components: &Components) -> String {
+        let layout = &components.layout;
+        let mut lines = layout.preamble.clone();
+        let mut written = vec![false; components.sections.len()];
+
+        for (id, blank_lines) in &layout.order {
+            let section_lines = match id {
+                SectionId::Script => self.plug_script(&components.script),
+                SectionId::Styles => self.plug_v4(&components.v4, &layout.styles_header),
+                SectionId::Events => self.plug_events(&components.events),
+                SectionId::Other(name) => {
+                    let section = components.sections.iter()
+                        .enumerate()
+                        .find(|(i, section)| !written[*i] && &section.name == name);
+                    match section {
+                        Some((i, section)) => {
+                            written[i] = true;
+                            self.plug_section(section)
+                        },
+                        None => continue,
+                    }
+                }
+            };
+            lines.extend(section_lines);
+            lines.resize(lines.len() + *blank_lines, String::new());
+        }
 
-        for pair in scriptinfo {
-            contents.push_str(&(pair[0].to_owned() + pair[1] + "\n"))
+        // sections added after the file was read go to the end.
+        for (section, _) in components.sections.iter().zip(written).filter(|(_, written)| !written) {
+            if lines.last().is_some_and(|line| !line.is_empty()) {
+                lines.push(String::new());
+            }
+            lines.extend(self.plug_section(section));
         }
-        contents
+
+        let mut total_data = lines.join(&layout.line_ending);
+        if layout.trailing_newline && !lines.is_empty() {
+            total_data.push_str(&layout.line_ending);
+        }
+        if layout.bom {
+            total_data.insert(0, '\u{feff}');
+        }
+        total_data
     }
 
-    fn combine_components(&self, components: &Components) -> String {
-        let components = components.clone();
-        let script = components.script;
-        let v4 = components.v4;
-        let events = components.events;
-        let scriptinfo  = script.get_key_values();
-
-        let script_data = &self.stringify_script(scriptinfo);
-        let v4_data = &self.plug_v4(v4);
-        let event_data = &self.plug_events(events);
-        let total_data = format!("{}\n\n{}\n\n{}", script_data, v4_data, event_data);
-
-        return total_data;
-    }
-
-    fn _plug_script(&self, script_lines: Vec<String>, scriptinfo: ScriptInfo) -> String {
-        let mut new_lines = script_lines.clone();
-        let mut total_lines = String::new();
-        let script_type = scriptinfo.scripttype.unwrap();
-        let playresx = scriptinfo.playresx.unwrap();
-        let playresy = scriptinfo.playresy.unwrap();
-        let scaledborderandshadow = scriptinfo.scaledborderandshadow.unwrap();
-        let ycbcr_matrix = scriptinfo.ycbcr_matrix.unwrap();
-
-
-        for (i, line) in script_lines.iter().enumerate() {
-            if line.starts_with(SCRIPT_TYPE) {
-                new_lines[i] = line[..SCRIPT_TYPE.len()].to_owned() + &script_type + "\n";
-                continue
-            } else if line.starts_with(SCRIPT_PLAYRESX){
-                new_lines[i] = line[..SCRIPT_PLAYRESX.len()].to_owned() + &playresx + "\n";
-                continue
-            } else if line.starts_with(SCRIPT_PLAYRESY){
-                new_lines[i] = line[..SCRIPT_PLAYRESY.len()].to_owned() + &playresy + "\n";
-                continue;
-            } else if line.starts_with(SCRIPT_SCALEDBORDERANDSHADOW){
-                new_lines[i] = line[..SCRIPT_SCALEDBORDERANDSHADOW.len()].to_owned() + &scaledborderandshadow + "\n";
-                continue;
-            } else if line.starts_with(SCRIPT_YCBCR_MATRIX){
-                new_lines[i] = line[..SCRIPT_YCBCR_MATRIX.len()].to_owned() + &ycbcr_matrix + "\n";
-                continue;
+    /// Put the raw lines of a section back in between its `Format: ` line and entries.
+    fn merge_raw_lines(&self, entries: Vec<String>, raw_lines: &[(usize, String)]) -> Vec<String> {
+        let mut lines = Vec::new();
+        let entry_count = entries.len();
+
+        for (i, entry) in entries.into_iter().enumerate() {
+            lines.extend(raw_lines.iter().filter(|(position, _)| *position == i).map(|(_, line)| line.clone()));
+            lines.push(entry);
+        }
+        lines.extend(raw_lines.iter().filter(|(position, _)| *position >= entry_count).map(|(_, line)| line.clone()));
+        lines
+    }
+
+    /// Write the entries of a section which were not modified as they were read.
+    fn restore_verbatim(&self, entries: Vec<String>, verbatim: &[(String, String)]) -> Vec<String> {
+        let mut verbatim = verbatim.to_vec();
+        entries.into_iter()
+            .map(|entry| match verbatim.iter().position(|(written, _)| *written == entry) {
+                Some(i) => verbatim.remove(i).1,
+                None => entry,
+            })
+            .collect()
+    }
+
+    /// The lines of a section which are not written the way they were read, with the line they
+    /// are written as.
+    fn changed_lines(&self, written: &[String], read: &[String]) -> Vec<(String, String)> {
+        written.iter().zip(read)
+            .filter(|(written, read)| written != read)
+            .map(|(written, read)| (written.clone(), read.clone()))
+            .collect()
+    }
+
+    fn plug_script(&self, scriptinfo: &ScriptInfo) -> Vec<String> {
+        let mut lines = vec![SCRIPT_HEADER.to_string()];
+
+        for line in &scriptinfo.lines {
+            match line {
+                ScriptLine::Pair(key, separator, value) => lines.push(format!("{}{}{}", key, separator, value)),
+                ScriptLine::Raw(raw) => lines.push(raw.clone()),
             }
         }
+        lines
+    }
+
+    fn plug_v4(&self, styles: &Styles, header: &str) -> Vec<String> {
+        let mut entries = vec![FORMAT_HEAD.to_string() + &styles.format.join(", ")];
 
-        for line in new_lines {
-            total_lines.push_str(line.as_str());
+        for style in styles.iter() {
+            let values: Vec<String> = styles.format.iter()
+                .map(|column| style.get_field(column).unwrap_or_default())
+                .collect();
+            entries.push(V4_STYLE_HEAD.to_string() + &values.join(","));
         }
 
-        return total_lines;
+        let mut lines = vec![header.to_string()];
+        lines.extend(self.merge_raw_lines(self.restore_verbatim(entries, &styles.verbatim), &styles.raw_lines));
+        lines
+    }
+
+    fn plug_events(&self, event_info: &Events) -> Vec<String> {
+        let mut entries = vec![FORMAT_HEAD.to_string() + &event_info.format.join(", ")];
+
+        for dialogue in &event_info.dialogues.dialogues {
+            entries.push(dialogue.to_line(&event_info.format));
+        }
+
+        let mut lines = vec![EVENTS_HEADER.to_string()];
+        lines.extend(self.merge_raw_lines(self.restore_verbatim(entries, &event_info.verbatim), &event_info.raw_lines));
+        lines
+    }
+
+    fn plug_section(&self, section: &Section) -> Vec<String> {
+        let mut lines = vec![format!("[{}]", section.name)];
+        lines.extend(section.lines.iter().cloned());
+        lines
+    }
+
+    fn parse_format(&self, line: &str) -> Option<Vec<String>> {
+        let columns = line.strip_prefix(FORMAT_HEAD.trim_end())?;
+        Some(format_columns(&columns.split(',').collect::<Vec<&str>>()))
     }
 
-    fn plug_v4(&self, v4_info: V4Format) -> String {
-        let array = v4_info.get_array();
-        let mut values = Vec::new();
-        let mut v4_lines = Vec::new();
-        let mut total_v4 = String::new();
-        v4_lines.push("[V4+ Styles]\n".to_string());
-        v4_lines.push("Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n".to_string());
-        v4_lines.push(V4_STYLE_HEAD.to_string());
+    fn get_each_components(&self, file_contents: String) -> std::result::Result<Components, ParseError> {
+        let mut components = AssFile::new().components;
+        let mut layout = Layout {
+            order: Vec::new(),
+            ..Layout::default()
+        };
 
-        for (i, value) in array.into_iter().enumerate() {
-            let style_val = value.clone().unwrap();
-            if i < (array.len()-1) {
-                values.push(style_val + ",");
+        let contents = match file_contents.strip_prefix('\u{feff}') {
+            Some(contents) => {
+                layout.bom = true;
+                contents
+            },
+            None => file_contents.as_str(),
+        };
+        if contents.contains("\r\n") {
+            layout.line_ending = "\r\n".to_string();
+        }
+        let mut lines: Vec<&str> = contents.split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .collect();
+        layout.trailing_newline = lines.last() == Some(&"");
+        if layout.trailing_newline {
+            lines.pop();
+        }
+
+        // the header, the line number of the first line after it and the lines of each section.
+        let mut sections: Vec<(String, usize, Vec<String>)> = Vec::new();
+        for (i, line) in lines.into_iter().enumerate() {
+            if line.starts_with('[') && line.ends_with(']') {
+                sections.push((line.to_string(), i + 2, Vec::new()));
             } else {
-                values.push(style_val);
+                match sections.last_mut() {
+                    Some((_, _, section_lines)) => section_lines.push(line.to_string()),
+                    None => layout.preamble.push(line.to_string()),
+                }
             }
         }
 
-        values.push("\n".to_string());
+        // SSA v4 files have a `[V4 Styles]` section instead of `[V4+ Styles]`.
+        let ssa = !sections.iter().any(|(header, _, _)| header == V4_HEADER)
+            && sections.iter().any(|(header, _, _)| header == SSA_V4_HEADER);
+        let styles_header = if ssa { SSA_V4_HEADER } else { V4_HEADER };
+
+        for required in [SCRIPT_HEADER, styles_header, EVENTS_HEADER] {
+            if !sections.iter().any(|(header, _, _)| header == required) {
+                return Err(ParseError::MissingSection(required.to_string()));
+            }
+        }
 
-        v4_lines.append(&mut values); 
+        for (header, first_line, mut section_lines) in sections {
+            let blank_lines = section_lines.iter().rev().take_while(|line| line.is_empty()).count();
+            section_lines.truncate(section_lines.len() - blank_lines);
+
+            let id = match header.as_str() {
+                SCRIPT_HEADER => {
+                    components.script = self.parse_script(section_lines);
+                    SectionId::Script
+                },
+                header if header == styles_header => {
+                    components.v4 = self.parse_v4(section_lines.clone(), first_line)?;
+                    components.v4.verbatim = self.changed_lines(&self.plug_v4(&components.v4, header)[1..], &section_lines);
+                    SectionId::Styles
+                },
+                EVENTS_HEADER => {
+                    components.events = self.parse_event(section_lines.clone(), first_line)?;
+                    components.events.verbatim = self.changed_lines(&self.plug_events(&components.events)[1..], &section_lines);
+                    SectionId::Events
+                },
+                _ => {
+                    let name = header[1..header.len()-1].to_string();
+                    components.sections.push(Section {
+                        name: name.clone(),
+                        lines: section_lines,
+                    });
+                    SectionId::Other(name)
+                },
+            };
+            layout.order.push((id, blank_lines));
+        }
 
-        for line in v4_lines {
-            total_v4.push_str(line.as_str());
+        components.layout = layout;
+        if ssa {
+            self.upgrade_ssa(&mut components);
         }
-        return total_v4;
+        Ok(components)
     }
 
-    fn plug_events(&self, event_info: Events) -> String {
-        let mut lines = Vec::new();
-        let mut total_events = String::new();
-        let dialogues = event_info.dialogues.dialogues;
-        lines.push(EVENTS_HEADER.to_string() + "\n");
-        lines.push("Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text".to_string() + "\n");
+    /// Turn the components read from an SSA v4 file into those of a `v4.00+` file. Styles get
+    /// the `[V4+ Styles]` columns, and the `Marked` column of events becomes `Layer`.
+    fn upgrade_ssa(&self, components: &mut Components) {
+        components.script.set_scripttype("v4.00+");
 
-        for dialogue in dialogues {
-            let dialogue_line = dialogue.to_string();
-            lines.push(dialogue_line);
+        components.v4.format = format_columns(&V4_FORMAT);
+        for style in components.v4.iter_mut() {
+            style.upgrade_ssa();
         }
-        for line in lines {
-            total_events.push_str(line.as_str());
+
+        let events = &mut components.events;
+        let has_layer = events.format.iter().any(|column| column.eq_ignore_ascii_case("Layer"));
+        events.format = events.format.iter()
+            .filter(|column| !(has_layer && column.eq_ignore_ascii_case(SSA_MARKED)))
+            .map(|column| if column.eq_ignore_ascii_case(SSA_MARKED) { "Layer".to_string() } else { column.clone() })
+            .collect();
+        for dialogue in events.dialogues.dialogues.iter_mut() {
+            dialogue.event.extra.retain(|(name, _)| !name.eq_ignore_ascii_case(SSA_MARKED));
+            dialogue.event.layer.get_or_insert_with(|| "0".to_string());
         }
-        
-        return total_events;
-    }
-
-    fn get_each_components(&self, file_contents: String) -> Components {
-        let lines:Vec<&str> = file_contents.split("\n").collect();
-        let script_lines = &self.get_info(&lines, SCRIPT_HEADER);
-        let v4_lines = &self.get_info(&lines, V4_HEADER);
-        let events_lines = &self.get_info(&lines, EVENTS_HEADER);
-
-        let script = self.parse_script(script_lines.to_vec()).unwrap();
-        let v4 = self.parse_v4(v4_lines.to_vec()).unwrap();
-        let events = self.parse_event(events_lines.to_vec()).unwrap();
-
-        Components {
-            script,
-            v4,
-            events,
-        }.clone()
-    }
-    fn parse_script(&self, script_lines: Vec<String>) -> Option<ScriptInfo> {
-        let mut script_type: Option<String>= None;
-        let mut script_playerresx: Option<String>= None;
-        let mut script_playerresy: Option<String>= None;
-        let mut script_scaledborderandshadow: Option<String>= None;
-        let mut script_ycbcr_matrix: Option<String>= None;
-
-        for line in &script_lines {
-            if line.starts_with(SCRIPT_TYPE) {
-                script_type = Some(line[SCRIPT_TYPE.len()..].to_owned());
-                continue
-            } else if line.starts_with(SCRIPT_PLAYRESX){
-                script_playerresx= Some(line[SCRIPT_PLAYRESX.len()..].to_owned());
-                continue
-            } else if line.starts_with(SCRIPT_PLAYRESY){
-                script_playerresy= Some(line[SCRIPT_PLAYRESY.len()..].to_owned());
-                continue;
-            } else if line.starts_with(SCRIPT_SCALEDBORDERANDSHADOW){
-                script_scaledborderandshadow = Some(line[SCRIPT_SCALEDBORDERANDSHADOW.len()..].to_owned());
-                continue;
-            } else if line.starts_with(SCRIPT_YCBCR_MATRIX){
-                script_ycbcr_matrix = Some(line[SCRIPT_YCBCR_MATRIX.len()..].to_owned());
-                continue;
+    }
+
+    /// The components of a file written in the SubStation Alpha v4 format. See `AssFile::to_ssa`.
+    fn downgrade_ssa(&self, components: &Components) -> Components {
+        let mut components = components.clone();
+        components.layout.styles_header = SSA_V4_HEADER.to_string();
+        components.script.set_scripttype("v4.00");
+
+        let styles = &mut components.v4;
+        styles.format = format_columns(&SSA_V4_FORMAT);
+        styles.styles = styles.iter().map(V4Format::downgrade_ssa).collect();
+
+        let events = &mut components.events;
+        events.format = format_columns(&SSA_EVENT_FORMAT);
+        for dialogue in events.dialogues.dialogues.iter_mut() {
+            let mut ssa = dialogue.clone().set_field(SSA_MARKED, "Marked=0");
+            if let Some(mut text) = ssa.get_tagged_text().filter(|text| text.tags().any(|tag| matches!(tag, Tag::Alignment(_)))) {
+                for tag in text.tags_mut() {
+                    if let Tag::Alignment(alignment) = tag {
+                        *tag = Tag::Unknown(format!("a{}", alignment.ssa()));
+                    }
+                }
+                ssa = ssa.set_tagged_text(&text);
             }
+            *dialogue = ssa;
         }
-        println!("{:?}, {:?}, {:?}, {:?} {:?}", script_type, 
-                 script_playerresx,
-                 script_playerresy,
-                 script_scaledborderandshadow,
-                 script_ycbcr_matrix);
+        components
+    }
 
+    fn parse_script(&self, script_lines: Vec<String>) -> ScriptInfo {
         let mut scriptinfo = ScriptInfo::new();
-        let script_info = scriptinfo.
-            set_scripttype(&script_type.unwrap()).
-            set_playresx(&script_playerresx.unwrap()).
-            set_playresy(&script_playerresy.unwrap()).
-            set_scaledborderandshadow(&script_scaledborderandshadow.unwrap()).
-            set_ycbcr_matrix(&script_ycbcr_matrix.unwrap()).clone();
-
-        Some(script_info)
-}
-    fn parse_event(&self, event_lines: Vec<String>) -> Option<Events>{
-        // let mut events = Vec::new();
-        let mut raw_dialogues = Vec::new();
+
+        for line in script_lines {
+            let pair = match line.split_once(':') {
+                Some((key, value)) if !line.starts_with(';') => {
+                    let trimmed = value.trim_start();
+                    let separator = format!(":{}", &value[..value.len() - trimmed.len()]);
+                    Some(ScriptLine::Pair(key.to_string(), separator, trimmed.to_string()))
+                },
+                _ => None,
+            };
+            scriptinfo.lines.push(pair.unwrap_or(ScriptLine::Raw(line)));
+        }
+        scriptinfo
+    }
+
+    /// Check that every column in `required` is part of a `Format: ` line.
+    fn check_format(&self, format: &[String], required: &[&str], line: usize, text: &str) -> std::result::Result<(), ParseError> {
+        for key in required {
+            if !format.iter().any(|column| column.eq_ignore_ascii_case(key)) {
+                return Err(ParseError::MissingKey {
+                    line,
+                    key: key.to_string(),
+                    text: text.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Split the values of an event line. The last column (`Text`) holds everything after the
+    /// comma preceding it, so it can contain commas itself.
+    fn split_event<'a>(&self, values: &'a str, columns: usize) -> Vec<&'a str> {
+        values.splitn(columns.max(1), ',').collect()
+    }
+
+    fn parse_event(&self, event_lines: Vec<String>, first_line: usize) -> std::result::Result<Events, ParseError> {
+        let mut events = Events::new();
         let mut dialogues = Vec::new();
+        let mut entries = 0;
+        let mut found_format = false;
         
-        for line in event_lines {
-            if line.starts_with(EVENT_HEAD) {
-                raw_dialogues.push(line[EVENT_HEAD.len()..].to_string());
+        for (i, line) in event_lines.into_iter().enumerate() {
+            let line_number = first_line + i;
+            if let Some(format) = self.parse_format(&line) {
+                self.check_format(&format, &["Start", "End", "Text"], line_number, &line)?;
+                events.format = format;
+                found_format = true;
+            } else if let Some((kind, dialogue)) = EventKind::strip_head(&line) {
+                if !found_format {
+                    return Err(ParseError::MissingKey {
+                        line: line_number,
+                        key: FORMAT_HEAD.trim_end_matches(": ").to_string(),
+                        text: line,
+                    });
+                }
+                let splitted_dialogue = self.split_event(dialogue, events.format.len());
+                if splitted_dialogue.len() != events.format.len() {
+                    return Err(ParseError::ColumnCount {
+                        line: line_number,
+                        expected: events.format.len(),
+                        found: splitted_dialogue.len(),
+                        text: line,
+                    });
+                }
+                let mut dialogue = Dialogue::new().set_kind(kind);
+                for (column, value) in events.format.iter().zip(splitted_dialogue) {
+                    dialogue = dialogue.set_field(column, value);
+                }
+                for time in [dialogue.get_field("Start"), dialogue.get_field("End")].into_iter().flatten() {
+                    if time.parse::<Timestamp>().is_err() {
+                        return Err(ParseError::BadTimestamp {
+                            line: line_number,
+                            text: time,
+                        });
+                    }
+                }
+                dialogues.push(dialogue);
+            } else {
+                events.raw_lines.push((entries, line));
+                continue;
             }
+            entries += 1;
         }
-        for dialogue in &raw_dialogues {
-            let splitted_dialogue: Vec<&str> = dialogue.split(',').collect();
-            let dialogue = Dialogue::new().
-                set_layer(splitted_dialogue[0]).
-                set_start(splitted_dialogue[1]).
-                set_end(splitted_dialogue[2]).
-                set_style(splitted_dialogue[3]).
-                set_name(splitted_dialogue[4]).
-                set_marginl(splitted_dialogue[5]).
-                set_marginr(splitted_dialogue[6]).
-                set_marginv(splitted_dialogue[7]).
-                set_effect(splitted_dialogue[8]).
-                set_text(splitted_dialogue[9]);
-            
-            dialogues.push(dialogue);
-        }
-
-        let dialogues = Dialogues {
-            dialogues,
-        };
 
-        return Some(Events {
+        events.dialogues = Dialogues {
             dialogues,
-        })
-
+        };
 
+        Ok(events)
     }
-    fn parse_v4(&self, v4_lines: Vec<String>) -> Option<V4Format>{
-        let mut style_line: Option::<String> = None;
-        for line in &v4_lines {
-            if line.starts_with(V4_STYLE_HEAD) {
-                style_line = Some(line[V4_STYLE_HEAD.len()..].to_string());
-                break;
-            }
-        }
-        if let Some(style_data) = style_line {
-            let values: Vec<&str> = style_data.split(',').collect();
-            println!("{:?}", values);
-
-            let v4format = V4Format::new().
-                set_name(values[0]).
-                set_fontname(values[1]).
-                set_fontsize(values[2]).
-                set_primarycolour(values[3]).
-                set_secondarycolour(values[4]).
-                set_outlinecolour(values[5]).
-                set_backcolour(values[6]).
-                set_bold(values[7]).
-                set_italic(values[8]).
-                set_underline(values[9]).
-                set_strikeout(values[10]).
-                set_scalex(values[11]).
-                set_scaley(values[12]).
-                set_spacing(values[13]).
-                set_angle(values[14]).
-                set_borderstyle(values[15]).
-                set_outline(values[16]).
-                set_shadow(values[17]).
-                set_alignment(values[18]).
-                set_marginl(values[19]).
-                set_marginr(values[20]).
-                set_marginv(values[22]).
-                set_encoding(values[22]).clone();
-
-            return Some(v4format);
-        } else {
-            eprintln!("Unable to parse v4!");
-            println!("{:?}", &v4_lines);
-            return None
-        }
-//["Default", "Arial", "16", "&Hffffff", "&Hffffff", "&H0", "&H0", "0", "0", "0", "0", "100", "100", "0", "0", "1", "1", "0", "2", "10", "10", "10", "1"]
-    }
-    fn get_info(&self, lines: &Vec<&str>, header: &str) -> Vec<String> {
-        let mut script_lines = Vec::new();
-        let mut found_script_header = false;
-        for line in lines {
-            let line = if line.ends_with('\n') {
-                &line[..line.len()-1]
-            } else if line.ends_with('\r'){
-                &line[..line.len()-1]
-            }else if line.ends_with("\r\n"){
-                &line[..line.len()-2]
-            }else {
-                line
-            };
-            if line == header{
-                found_script_header = true;
-                script_lines.push(line.to_string());
-                script_lines.push("\n".to_string());
-                continue
-            }
-            if found_script_header {
-                if line.starts_with('[') {
-                    break;
-                } else if line.starts_with(';') {
-                    continue;
-                } else {
-                    script_lines.push(line.to_string());
+    fn parse_v4(&self, v4_lines: Vec<String>, first_line: usize) -> std::result::Result<Styles, ParseError> {
+        let mut styles = Styles::new();
+        let mut entries = 0;
+        let mut found_format = false;
+
+        for (i, line) in v4_lines.into_iter().enumerate() {
+            let line_number = first_line + i;
+            if let Some(format) = self.parse_format(&line) {
+                self.check_format(&format, &["Name"], line_number, &line)?;
+                styles.format = format;
+                found_format = true;
+            } else if let Some(style_data) = strip_head(&line, V4_STYLE_HEAD) {
+                if !found_format {
+                    return Err(ParseError::MissingKey {
+                        line: line_number,
+                        key: FORMAT_HEAD.trim_end_matches(": ").to_string(),
+                        text: line,
+                    });
                 }
+                let values: Vec<&str> = style_data.split(',').collect();
+                if values.len() != styles.format.len() {
+                    return Err(ParseError::ColumnCount {
+                        line: line_number,
+                        expected: styles.format.len(),
+                        found: values.len(),
+                        text: line,
+                    });
+                }
+
+                let mut v4format = V4Format::new();
+                for (column, value) in styles.format.iter().zip(values) {
+                    v4format.set_field(column, value);
+                }
+                styles.styles.push(v4format);
             } else {
+                styles.raw_lines.push((entries, line));
                 continue;
             }
+            entries += 1;
         }
-        return script_lines;
+        Ok(styles)
     }
 }
 
 impl AssFile {
     /// Construct `AssFile` from an existing `.ass` file. 
+    /// A `ParseError` carrying the line number and the offending text is returned when the file
+    /// cannot be read or is not a valid `.ass` file.
+    /// SubStation Alpha v4 (`.ssa`) files are read into the same model, see `AssFile::to_ssa`.
     ///
     /// # Example
     /// ```rust
     /// # use ass_parser::AssFile;
-    /// let mut ass_file = ass_parser::AssFile::from_file("src/subtitles.ass".to_string()).expect("error while reading file.");
+    /// match AssFile::from_file("examples/subtitles.ass") {
+    ///     Ok(ass_file) => println!("{} styles", ass_file.v4.len()),
+    ///     Err(e) => eprintln!("skipping bad input: {}", e),
+    /// }
     /// ```
-    pub fn from_file(filename: &str) -> std::result::Result<AssFile, std::io::Error> {
-        let file_contents = get_contents(&filename);
+    pub fn from_file(filename: &str) -> std::result::Result<AssFile, ParseError> {
+        let contents = get_contents(filename)?;
         let parser = Parser::new();
-        match file_contents {
-            Ok(contents) => {
-                let components = parser.get_each_components(contents);
-                Ok(
-                    Self{
-                    _ass_file: filename.to_string(),
-                    components,
-                })
-            },
-            Err(e) => {
-                return Err(e)
-            }
-        }
+        let components = parser.get_each_components(contents)?;
+
+        Ok(
+            Self{
+            _ass_file: filename.to_string(),
+            components,
+        })
     }
 
 }
@@ -1609,17 +3238,17 @@ impl AssFile {
     /// ```rust
     /// use hex_color::HexColor;
     /// use ass_parser;
-    /// use ass_parser::{AssFile, V4Format, AssFileOptions};
+    /// use ass_parser::{AssFile, V4Format, AssColor};
 
     ///
-    /// fn main() -> Result<(), std::io::Error>{
+    /// fn main() -> Result<(), ass_parser::ParseError>{
     ///    let mut ass_file = ass_parser::AssFile::from_file("subtitles.ass".to_string())?;
     ///    ass_file.components.script 
-    ///        .set_scripttype("v4.00+".to_string())
-    ///        .set_playresx("384".to_string())
-    ///        .set_playresy("288".to_string())
-    ///        .set_scaledborderandshadow("yes".to_string())
-    ///        .set_ycbcr_matrix("None".to_string());
+    ///        .set_scripttype("v4.00+")
+    ///        .set_playresx(384)
+    ///        .set_playresy(288)
+    ///        .set_scaledborderandshadow(true)
+    ///        .set_ycbcr_matrix("None");
     ///
     ///    ass_file.components.v4.set_v4(V4Format::default());
     ///
@@ -1633,49 +3262,78 @@ impl AssFile {
         let file_data = parser.combine_components(components);
         write_contents(filename, &file_data);
     }
-}
 
-impl AssFileOptions {
-}
-
-impl AssFileOptions{
-    /// Get BB:GG:RR representation of colors in Hexadecimal form
-    pub fn get_ass_color(color: HexColor) -> String {
-        let red = color.r;
-        let green = color.g;
-        let blue = color.b;
+    /// save the dialogues of an `AssFile` to a SubRip (`.srt`) file. See `Events::to_srt`.
+    pub fn save_srt(file_components: &AssFile, filename: &str) {
+        write_contents(filename, &file_components.components.events.to_srt());
+    }
 
-        let red_hex = format!("{:x}", red);
-        let green_hex = format!("{:x}", green);
-        let blue_hex = format!("{:x}", blue);
+    /// Write the file in the SubStation Alpha v4 (`.ssa`) format, as read by older programs.
+    /// Styles are written in a `[V4 Styles]` section, where the outline colour is the
+    /// `TertiaryColour` and alignments use the SSA numbering, and events get a `Marked=0` column.
+    /// What SSA v4 cannot describe is lost: the underline, strikeout, scaling, spacing and angle
+    /// of the styles and the layer of the events. `\an` tags are written as `\a` tags.
+    ///
+    /// SSA v4 files are read by `AssFile::from_file` like `.ass` files, into the same model.
+    ///
+    /// ```rust
+    /// use ass_parser::{Alignment, AssFile, V4Format};
+    ///
+    /// let mut ass_file = AssFile::new();
+    /// ass_file.components.v4.set_v4(V4Format::default().set_alignment(Alignment::TopCenter).clone());
+    ///
+    /// let ssa = ass_file.to_ssa();
+    /// assert!(ssa.contains("ScriptType: v4.00\n"));
+    /// assert!(ssa.contains("[V4 Styles]\n"));
+    /// assert!(ssa.contains("Style: Default,Arial,16,16777215,16777215,0,0,0,0,1,1,0,6,10,10,10,0,1\n"));
+    /// ```
+    pub fn to_ssa(&self) -> String {
+        let parser = Parser::new();
+        parser.combine_components(&parser.downgrade_ssa(&self.components))
+    }
 
-        let reversed_hex_color = format!("{}{}{}", blue_hex, green_hex, red_hex);
+    /// save an `AssFile` to a SubStation Alpha v4 (`.ssa`) file. See `AssFile::to_ssa`.
+    pub fn save_ssa(file_components: &AssFile, filename: &str) {
+        write_contents(filename, &file_components.to_ssa());
+    }
 
-        // let mut ass_format_color = format!(r"\c&H{}&", reversed_hex_color);
-        let ass_format_color = format!("&H{}", reversed_hex_color);
-        // ass_format_color.push('}');
-        // ass_format_color = "{".to_owned() + &ass_format_color;
+    /// save the dialogues of an `AssFile` to a MicroDVD (`.sub`) file, timed in the frames of
+    /// `timecodes`. See `Events::to_microdvd`.
+    pub fn save_microdvd(file_components: &AssFile, filename: &str, timecodes: &Timecodes) {
+        write_contents(filename, &file_components.components.events.to_microdvd(timecodes).to_string());
+    }
 
-        return ass_format_color;
+    /// save the events of an `AssFile` to a WebVTT (`.vtt`) file. See `AssFile::to_vtt`.
+    pub fn save_vtt(file_components: &AssFile, filename: &str) {
+        write_contents(filename, &file_components.to_vtt().to_string());
     }
 
+    /// save the dialogues of an `AssFile` to an enhanced LRC (`.lrc`) file.
+    /// See `Events::to_lrc`.
+    pub fn save_lrc(file_components: &AssFile, filename: &str) {
+        write_contents(filename, &file_components.components.events.to_lrc().to_string());
+    }
 
-    pub fn get_ass_color_text(color: HexColor) -> String {
-        let red = color.r;
-        let green = color.g;
-        let blue = color.b;
+    /// save the styles and dialogues of an `AssFile` to a TTML (`.ttml`) file.
+    /// See `AssFile::to_ttml`.
+    pub fn save_ttml(file_components: &AssFile, filename: &str) {
+        write_contents(filename, &file_components.to_ttml());
+    }
+}
 
-        let red_hex = format!("{:x}", red);
-        let green_hex = format!("{:x}", green);
-        let blue_hex = format!("{:x}", blue);
+impl AssFileOptions {
+}
 
-        let reversed_hex_color = format!("{}{}{}", blue_hex, green_hex, red_hex);
+impl AssFileOptions{
+    /// Get the `&HAABBGGRR` representation of colors used by styles.
+    pub fn get_ass_color(color: HexColor) -> String {
+        AssColor::from(color).to_string()
+    }
 
-        let mut ass_format_color = format!(r"\c&H{}&", reversed_hex_color);
-        ass_format_color.push('}');
-        ass_format_color = "{".to_owned() + &ass_format_color;
 
-        return ass_format_color;
+    /// Get the `{\c&HBBGGRR&}` override tag colouring the text of a dialogue.
+    pub fn get_ass_color_text(color: HexColor) -> String {
+        format!("{{\\c{}}}", AssColor::from(color).to_tag())
     }
 
     fn _change_ass_subtitle_color(ass_file: &str, color: HexColor) -> std::result::Result<(), std::io::Error>{
@@ -1730,6 +3388,16 @@ impl AssFileOptions{
 
 //{\c&He3cb44&}
 
+fn format_columns(columns: &[&str]) -> Vec<String> {
+    columns.iter().map(|column| column.trim().to_string()).collect()
+}
+
+/// Strip a head like `Style: ` from the start of a line, with or without the space after the `:`.
+fn strip_head<'a>(line: &'a str, head: &str) -> Option<&'a str> {
+    let values = line.strip_prefix(head.trim_end())?;
+    Some(values.strip_prefix(' ').unwrap_or(values))
+}
+
 fn check_path_exists(path: &str) -> bool {
     fs::metadata(path).is_ok()
 }
@@ -1770,6 +3438,7 @@ fn get_contents(filename: &str) -> std::result::Result<String, std::io::Error>{
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     #[test]
     fn test_file_contents() {
@@ -1777,13 +3446,13 @@ mod tests {
         let file_contents = get_contents("examples/rapgod.srt").unwrap();
 
         let srt_data = SrtData::new();
-        let srt_content = srt_data.parse_srt(file_contents);
+        let srt_content = srt_data.parse_srt(&file_contents).unwrap();
 
         let test_srt_content = SrtData { 
             index: "0".to_string(),
-			start: "0:00:01.50".to_string(),
-			end: "0:00:04.90".to_string(),
-			text: "Look, I was gonna go easy on you and not to hurt your feelings ".to_string(),
+			start: Timestamp::new(0, 0, 1, 50),
+			end: Timestamp::new(0, 0, 4, 90),
+			text: "Look, I was gonna go easy on\\Nyou and not to hurt your\\Nfeelings".to_string(),
          };
 
         assert_eq!(test_srt_content, srt_content[0]);
@@ -1791,9 +3460,746 @@ mod tests {
 
     #[test]
     fn test_from_file_wrong() {
-        let result = AssFile::from_file("asdfasdf").map_err(|e| e.kind());
-        let expected = std::result::Result::Err(std::io::ErrorKind::NotFound);
+        let result = AssFile::from_file("asdfasdf").map_err(|e| match e {
+            ParseError::Io(e) => Some(e.kind()),
+            _ => None,
+        });
+        let expected = std::result::Result::Err(Some(std::io::ErrorKind::NotFound));
 
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn test_multiple_styles_round_trip() {
+        let parser = Parser::new();
+        let v4_lines: Vec<String> = [
+            "[V4+ Styles]",
+            "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding",
+            "Style: Default,Arial,20,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,2,2,10,10,10,1",
+            "Style: Signs,Verdana,30,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,-1,0,0,0,100,100,0,0,1,2,0,8,10,10,25,1",
+        ].iter().map(|line| line.to_string()).collect();
+
+        let mut styles = parser.parse_v4(v4_lines[1..].to_vec(), 2).unwrap();
+        assert_eq!(2, styles.len());
+        assert_eq!(Some("25".to_string()), styles.get_style("Signs").unwrap().marginv);
+
+        assert_eq!(v4_lines, parser.plug_v4(&styles, V4_HEADER));
+
+        styles.rename_style("Signs", "Top");
+        assert!(styles.get_style("Signs").is_none());
+        assert!(styles.remove_style("Top").is_some());
+        assert_eq!(1, styles.len());
+    }
+
+    #[test]
+    fn test_format_line_column_order() {
+        let parser = Parser::new();
+        let v4_lines: Vec<String> = [
+            "[V4 Styles]",
+            "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, TertiaryColour, BackColour, Bold, Italic, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, AlphaLevel, Encoding",
+            "Style: Default,Tahoma,24,16777215,65535,65535,-2147483640,-1,0,1,1,2,2,30,30,30,0,0",
+        ].iter().map(|line| line.to_string()).collect();
+        let event_lines: Vec<String> = [
+            "[Events]",
+            "Format: Marked, Start, End, Style, Name, Text",
+            "Dialogue: Marked=0,0:00:01.00,0:00:02.00,Default,Bob,Hi",
+        ].iter().map(|line| line.to_string()).collect();
+
+        let styles = parser.parse_v4(v4_lines[1..].to_vec(), 2).unwrap();
+        let style = styles.get_style("Default").unwrap();
+        assert_eq!(Some("24".to_string()), style.get_field("fontsize"));
+        assert_eq!(Some("65535".to_string()), style.get_field("TertiaryColour"));
+        assert_eq!(v4_lines[1..], parser.plug_v4(&styles, V4_HEADER)[1..]);
+
+        let events = parser.parse_event(event_lines[1..].to_vec(), 2).unwrap();
+        let dialogue = &events.get_dialogues()[0];
+        assert_eq!(Some("Bob".to_string()), dialogue.get_name());
+        assert_eq!(Some("Marked=0".to_string()), dialogue.get_field("Marked"));
+        assert_eq!(event_lines, parser.plug_events(&events));
+    }
+
+    #[test]
+    fn test_lossless_round_trip() {
+        let contents = [
+            "\u{feff}[Script Info]",
+            "; Script generated by Aegisub 3.2.2",
+            "; http://www.aegisub.org/",
+            "Title: Default Aegisub file",
+            "Original Script:  Someone",
+            "Translation:Someone else",
+            "ScriptType: v4.00+",
+            "WrapStyle: 0",
+            "PlayResX: 1920",
+            "PlayResY: 1080",
+            "YCbCr Matrix: TV.709",
+            "",
+            "[Aegisub Project Garbage]",
+            "Last Style Storage: Default",
+            "Video File: episode.mkv",
+            "",
+            "[V4+ Styles]",
+            "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding",
+            "Style: Default,Arial,48,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,2,2,10,10,10,1",
+            "; signs go below",
+            "Style: Signs,Arial,48,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,2,8,10,10,10,1",
+            "",
+            "[Fonts]",
+            "fontname: custom_0.ttf",
+            "M(\"I]&%!\"",
+            "",
+            "[Events]",
+            "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text",
+            "Dialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Hello",
+            "",
+        ].join("\r\n");
+
+        let parser = Parser::new();
+        let mut components = parser.get_each_components(contents.clone()).unwrap();
+        assert_eq!(2, components.v4.len());
+        assert_eq!("Fonts", components.sections[1].name);
+        assert_eq!(contents, parser.combine_components(&components));
+
+        components.script.set_playresx(1280);
+        components.sections.push(Section::new("Graphics"));
+        let modified = parser.combine_components(&components);
+        assert!(modified.contains("\r\nPlayResX: 1280\r\n"));
+        assert!(modified.ends_with("Hello\r\n\r\n[Graphics]\r\n"));
+
+        let irregular = [
+            "[Script Info]",
+            "Title:Irregular",
+            "",
+            "[V4+ Styles]",
+            "Format:Name,Fontname ,  Fontsize",
+            "Style:Default,Arial,20",
+            "",
+            "[Events]",
+            "Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text",
+            "Dialogue:0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Hello",
+            "Dialogue:0,0:00:03.00,0:00:04.00,Default,,0,0,0,,World",
+            "Comment:  0,0:00:03.00,0:00:04.00,Default,,0,0,0,,Spaced",
+            "",
+        ].join("\n");
+        let mut components = parser.get_each_components(irregular.clone()).unwrap();
+        assert_eq!(3, components.events.get_dialogues().len());
+        assert_eq!(1, components.v4.len());
+        assert_eq!(irregular, parser.combine_components(&components));
+
+        components.events.dialogues.dialogues[1] = components.events.get_dialogues()[1].clone().set_text("Changed");
+        let modified = parser.combine_components(&components);
+        assert!(modified.contains("\nDialogue:0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Hello\nDialogue: 0,0:00:03.00,0:00:04.00,Default,,0,0,0,,Changed\n"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parser = Parser::new();
+        let header = "[Script Info]\nScriptType: v4.00+\n\n[V4+ Styles]\nFormat: Name, Fontname\nStyle: Default,Arial\n\n";
+
+        let result = parser.get_each_components(header.to_string());
+        assert!(matches!(result, Err(ParseError::MissingSection(section)) if section == EVENTS_HEADER));
+
+        let contents = header.to_string() + "[Events]\nDialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Hi\n";
+        let result = parser.get_each_components(contents);
+        assert!(matches!(result, Err(ParseError::MissingKey { line: 9, key, .. }) if key == "Format"));
+
+        let contents = header.to_string() + "[Events]\nFormat: Start, End, Text\nDialogue: 0:00:01.00\n";
+        let result = parser.get_each_components(contents);
+        assert!(matches!(result, Err(ParseError::ColumnCount { line: 10, expected: 3, found: 1, .. })));
+
+        let contents = header.to_string() + "[Events]\nFormat: Start, End, Text\nDialogue: 0:00:01:00,0:00:02.00,Hi\n";
+        let result = parser.get_each_components(contents);
+        assert!(matches!(result, Err(ParseError::BadTimestamp { line: 10, text }) if text == "0:00:01:00"));
+    }
+
+    #[test]
+    fn test_dialogue_text_with_commas() {
+        let parser = Parser::new();
+        let event_lines = vec![
+            "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text".to_string(),
+            "Dialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Hello, world, {\\pos(10,20)}again".to_string(),
+        ];
+
+        let events = parser.parse_event(event_lines.clone(), 1).unwrap();
+        let text = events.get_dialogues()[0].get_text();
+        assert_eq!(Some("Hello, world, {\\pos(10,20)}again".to_string()), text);
+        assert_eq!(event_lines, parser.plug_events(&events)[1..]);
+    }
+
+    #[test]
+    fn test_event_kinds() {
+        let parser = Parser::new();
+        let event_lines = vec![
+            "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text".to_string(),
+            "Comment: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Original, untranslated".to_string(),
+            "Dialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Translated".to_string(),
+            "Sound: 0,0:00:03.00,0:00:04.00,Default,,0,0,0,,bell.wav".to_string(),
+        ];
+
+        let events = parser.parse_event(event_lines.clone(), 1).unwrap();
+        let dialogues = events.get_dialogues();
+        assert!(dialogues[0].is_comment());
+        assert_eq!(Some("Original, untranslated".to_string()), dialogues[0].get_text());
+        assert_eq!(EventKind::Dialogue, dialogues[1].get_kind());
+        assert_eq!(EventKind::Sound, dialogues[2].get_kind());
+        assert_eq!(event_lines, parser.plug_events(&events)[1..]);
+
+        let comment = dialogues[1].clone().set_kind(EventKind::Comment);
+        assert_eq!("Comment: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Translated", comment.to_line(&events.format));
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let start: Timestamp = "0:00:01.5".parse().unwrap();
+        assert_eq!(Timestamp::new(0, 0, 1, 50), start);
+        assert_eq!(Timestamp::from_centiseconds(151), "0:00:01.505".parse().unwrap());
+        assert_eq!(Timestamp::from_millis(3_723_456), Timestamp::from_srt("01:02:03,456").unwrap());
+        assert_eq!("1:02:03.46", Timestamp::from_srt("01:02:03,456").unwrap().to_string());
+        for malformed in ["0:00:00:00", "00:00.50", "0:60:00.00", "a:00:00.00", "0:00:00.1234"] {
+            assert!(malformed.parse::<Timestamp>().is_err(), "{}", malformed);
+        }
+
+        assert_eq!(Timestamp::ZERO, start - Duration::from_secs(2));
+        assert_eq!(Timestamp::new(0, 0, 3, 0), start + Duration::from_millis(1500));
+        assert_eq!(Duration::from_millis(1500), Timestamp::new(0, 0, 3, 0) - start);
+
+        let dialogue = Dialogue::default().set_start(start).set_end(start + Duration::from_secs(1));
+        assert_eq!(Some(Timestamp::new(0, 0, 2, 50)), dialogue.get_end());
+        assert_eq!(Some("0:00:01.50".to_string()), dialogue.get_field("Start"));
+    }
+
+    #[test]
+    fn test_colors() {
+        let blue = AssColor::from(HexColor::rgb(0, 0, 255));
+        assert_eq!("&H00FF0000", blue.to_string());
+        assert_eq!("&HFF0000&", blue.to_tag());
+        assert_eq!(Ok(blue), "&HFF0000&".parse());
+        assert_eq!(Ok(AssColor::rgb(0, 0, 0)), "&H0".parse());
+        assert_eq!(Ok(AssColor::rgb(255, 255, 255)), "16777215".parse());
+        assert_eq!(Ok(AssColor::rgba(8, 0, 0, 0x80)), "-2147483640".parse());
+        assert!("&HGG".parse::<AssColor>().is_err());
+
+        let mut style = V4Format::default();
+        assert_eq!(Some("&H00FFFFFF".to_string()), style.get_field("PrimaryColour"));
+        assert_eq!(Some("&H00000000".to_string()), style.get_field("OutlineColour"));
+        style.set_backcolour(AssColor::rgba(1, 2, 3, 0x40));
+        assert_eq!(Some("&H40030201".to_string()), style.get_field("BackColour"));
+        assert_eq!(Some(AssColor::rgba(1, 2, 3, 0x40)), style.get_backcolour());
+
+        let dialogue = Dialogue::default().set_text("Hi").set_colour(AssColor::rgba(255, 0, 0, 0x80));
+        assert_eq!(Some("{\\c&H0000FF&\\1a&H80&}Hi".to_string()), dialogue.get_text());
+    }
+
+    #[test]
+    fn test_typed_style_fields() {
+        let mut style = V4Format::default();
+        style.set_fontsize(20.5)
+            .set_bold(true)
+            .set_italic(false)
+            .set_borderstyle(BorderStyle::OpaqueBox)
+            .set_alignment(Alignment::TopCenter)
+            .set_marginv(-5);
+        assert_eq!(Some("20.5".to_string()), style.get_field("Fontsize"));
+        assert_eq!(Some("-1".to_string()), style.get_field("Bold"));
+        assert_eq!(Some("0".to_string()), style.get_field("Italic"));
+        assert_eq!(Some("3".to_string()), style.get_field("BorderStyle"));
+        assert_eq!(Some("8".to_string()), style.get_field("Alignment"));
+        assert_eq!(Some(20.5), style.get_fontsize());
+        assert_eq!(Some(true), style.get_bold());
+        assert_eq!(Some(BorderStyle::OpaqueBox), style.get_borderstyle());
+        assert_eq!(Some(Alignment::TopCenter), style.get_alignment());
+        assert_eq!(Some(-5), style.get_marginv());
+
+        style.set_field("Fontsize", "big").set_field("Alignment", "10");
+        assert_eq!(None, style.get_fontsize());
+        assert_eq!(None, style.get_alignment());
+        assert_eq!(Some("big".to_string()), style.get_field("Fontsize"));
+    }
+
+    #[test]
+    fn test_script_info_keys() {
+        let parser = Parser::new();
+        let lines: Vec<String> = [
+            "; Script generated by Aegisub",
+            "Title: Episode 1",
+            "Original Script:  Someone",
+            "Translation:Someone else",
+            "ScriptType: v4.00+",
+            "WrapStyle: 2",
+            "PlayResX: 1920",
+            "PlayResY: 1080",
+            "ScaledBorderAndShadow: no",
+            "Timer: 100.0000",
+        ].iter().map(|line| line.to_string()).collect();
+
+        let mut script = parser.parse_script(lines.clone());
+        assert_eq!(lines, parser.plug_script(&script)[1..]);
+        assert_eq!(Some("Episode 1".to_string()), script.get_title());
+        assert_eq!(Some(WrapStyle::None), script.get_wrapstyle());
+        assert_eq!(Some(1920), script.get_playresx());
+        assert_eq!(Some(false), script.get_scaledborderandshadow());
+        assert_eq!(None, script.get_ycbcr_matrix());
+        assert_eq!(Some("Someone"), script.get_value("Original Script"));
+        assert_eq!(Some("Someone else"), script.get_value("Translation"));
+        assert_eq!(9, script.iter().count());
+
+        assert_eq!(Some("100.0000".to_string()), script.remove_value("timer"));
+        script.set_ycbcr_matrix("TV.709").set_playresy(720);
+        assert_eq!(
+            vec![("PlayResY", "720"), ("ScaledBorderAndShadow", "no"), ("YCbCr Matrix", "TV.709")],
+            script.iter().skip(6).collect::<Vec<_>>(),
+        );
+        script.set_value("translation", "Nobody");
+        assert!(parser.plug_script(&script).contains(&"Translation: Nobody".to_string()));
+    }
+
+    #[test]
+    fn test_override_tags() {
+        let line = r"{\b1\an8\c&H0000FF&\alpha&H80&\move(0,0,100,50,0,500)\t(0,1000,\frz360\clip(0,0,10,10))\iclip(1,m 0 0 l 10 0 10 10)}Hi{note\kf50}there\N{\p1}m 0 0 l 5 5{\p0\foo}";
+        let text = TaggedText::parse(line);
+        assert_eq!(line, text.to_string());
+        assert_eq!(7, text.segments.len());
+
+        let tags: Vec<&Tag> = text.tags().collect();
+        assert_eq!(Tag::Bold(1), *tags[0]);
+        assert_eq!(Tag::Alignment(Alignment::TopCenter), *tags[1]);
+        assert_eq!(Tag::Colour(1, AssColor::rgb(255, 0, 0)), *tags[2]);
+        assert_eq!(Tag::Alpha(0x80), *tags[3]);
+        assert_eq!(Tag::Move { from: (0.0, 0.0), to: (100.0, 50.0), times: Some((0, 500)) }, *tags[4]);
+        assert_eq!(Tag::Transform {
+            times: Some((0, 1000)),
+            accel: None,
+            tags: vec![Tag::RotationZ(360.0), Tag::Clip { inverse: false, shape: ClipShape::Rectangle(0.0, 0.0, 10.0, 10.0) }],
+        }, *tags[5]);
+        assert_eq!(Tag::Comment("note".to_string()), *tags[7]);
+        assert_eq!(Tag::Karaoke(KaraokeKind::Sweep, 50), *tags[8]);
+        assert_eq!(Tag::Unknown("foo".to_string()), *tags[11]);
+        assert_eq!(Segment::Text("m 0 0 l 5 5".to_string()), text.segments[5]);
+
+        assert_eq!("{a", TaggedText::parse("{a").to_string());
+        let dialogue = Dialogue::default().set_text(r"{\pos(1,2)\c&HFF&}Hi").set_colour(AssColor::rgb(0, 255, 0));
+        assert_eq!(Some(r"{\c&H00FF00&\pos(1,2)}Hi".to_string()), dialogue.get_text());
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let dialogue = Dialogue::default()
+            .set_text(r"{\an8}Hello,\Nworld{\i1}!\h{\p1}m 0 0 l 10 10{\p0}Bye\n");
+        assert_eq!(Some("Hello,\nworld!\u{a0}Bye\n".to_string()), dialogue.get_plain_text());
+
+        let events = Events::new()
+            .add_dialogue(Dialogue::default().set_text(r"{\b1}One"))
+            .create();
+        assert_eq!(vec!["".to_string(), "One".to_string()], events.get_plain_texts());
+    }
+
+    #[test]
+    fn test_drawing() {
+        let drawing: Drawing = "m 0 0 l 10 0 10 10 b 10 20 0 20 0 10 n 5 5 s 1 1 2 2 3 3 p 4 4 c".parse().unwrap();
+        assert_eq!(7, drawing.commands.len());
+        assert_eq!("m 0 0 l 10 0 10 10 b 10 20 0 20 0 10 n 5 5 s 1 1 2 2 3 3 p 4 4 c", drawing.to_string());
+        assert_eq!(Some((Point::new(0.0, 0.0), Point::new(10.0, 20.0))), drawing.bounding_box());
+        for malformed in ["m 0", "l 1 1 x 2", "b 0 0 1 1", "0 0", "m a b"] {
+            assert!(malformed.parse::<Drawing>().is_err(), "{}", malformed);
+        }
+
+        let mut arrow: Drawing = "m 0 0 l 10 0".parse().unwrap();
+        arrow.rotate(90.0, Point::new(0.0, 0.0));
+        assert_eq!("m 0 0 l 0 -10", arrow.to_string());
+
+        let dialogue = Dialogue::default().set_text(r"{\an7\pos(0,0)\p1}m 0 0 l 10 0 10 10{\p0}");
+        let mut square = dialogue.get_drawing().unwrap();
+        square.translate(1.0, 2.0);
+        let dialogue = dialogue.set_drawing(&square);
+        assert_eq!(Some(r"{\an7\pos(0,0)\p1}m 1 2 l 11 2 11 12{\p0}".to_string()), dialogue.get_text());
+        assert_eq!(Some(r"{\p1}m 1 2 l 11 2 11 12".to_string()), Dialogue::default().set_drawing(&square).get_text());
+    }
+
+    #[test]
+    fn test_time_shift() {
+        let dialogue = Dialogue::default()
+            .set_start(Timestamp::new(0, 0, 1, 0))
+            .set_end(Timestamp::new(0, 0, 3, 0))
+            .set_text(r"{\move(0,0,10,10)\fad(1500,200)\t(1000,2000,\fs20)\b1}Hi");
+
+        let later = dialogue.clone().shift(250, true);
+        assert_eq!(Some(Timestamp::new(0, 0, 1, 25)), later.get_start());
+        assert_eq!(Some(Timestamp::new(0, 0, 3, 25)), later.get_end());
+        assert_eq!(dialogue.get_text(), later.get_text());
+
+        let cut = dialogue.clone().shift(-1500, true);
+        assert_eq!(Some(Timestamp::ZERO), cut.get_start());
+        assert_eq!(Some(Timestamp::new(0, 0, 1, 50)), cut.get_end());
+        assert_eq!(Some(r"{\move(0,0,10,10,-500,1500)\fad(1000,200)\t(500,1500,\fs20)\b1}Hi".to_string()), cut.get_text());
+        assert_eq!(dialogue.get_text(), dialogue.clone().shift(-1500, false).get_text());
+
+        let karaoke = dialogue.clone().set_text(r"{\k50}Ka{\kf80}ra{\ko70}oke").shift(-2000, true);
+        assert_eq!(Some(r"{\k0}Ka{\kf30}ra{\ko70}oke".to_string()), karaoke.get_text());
+
+        let mut events = Events::new().add_dialogue(dialogue).create();
+        events.shift(-5000, false);
+        let shifted = &events.get_dialogues()[1];
+        assert_eq!((Some(Timestamp::ZERO), Some(Timestamp::ZERO)), (shifted.get_start(), shifted.get_end()));
+    }
+
+    #[test]
+    fn test_change_framerate() {
+        let dialogue = Dialogue::default()
+            .set_start(Timestamp::new(0, 20, 0, 0))
+            .set_end(Timestamp::new(0, 20, 2, 0))
+            .set_text(r"{\k10}Ka{\k10}ra{\k10}o{\t(0,1000,\fs20)}ke");
+
+        let mut events = Events::new().add_dialogue(dialogue.clone()).create();
+        assert!(events.change_framerate(25.0, 0.0, true).is_none());
+        assert!(events.change_framerate(f64::NAN, 24.0, true).is_none());
+        assert!(events.change_framerate(-25.0, 24.0, true).is_none());
+        assert_eq!(Some(Timestamp::new(0, 20, 0, 0)), events.get_dialogues()[1].get_start());
+        events.change_framerate(25.0, 24.0, true);
+        let converted = &events.get_dialogues()[1];
+        assert_eq!(Some(Timestamp::new(0, 20, 50, 0)), converted.get_start());
+        assert_eq!(Some(Timestamp::new(0, 20, 52, 8)), converted.get_end());
+        assert_eq!(Some(r"{\k10}Ka{\k11}ra{\k10}o{\t(0,1042,\fs20)}ke".to_string()), converted.get_text());
+
+        let untouched = dialogue.clone().scale_times(25.0 / 24.0, false);
+        assert_eq!(dialogue.get_text(), untouched.get_text());
+    }
+
+    #[test]
+    fn test_timecodes_and_keyframes() {
+        let v1 = Timecodes::parse("# timecode format v1\nAssume 25\n# slow part\n2,3,12.5\n").unwrap();
+        assert_eq!(80.0, v1.frame_millis(2));
+        assert_eq!(240.0, v1.frame_millis(4));
+        assert_eq!(280.0, v1.frame_millis(5));
+        assert_eq!(3, v1.frame_at(Timestamp::from_millis(230)));
+        assert_eq!(Timestamp::from_millis(200), v1.start_time(4));
+        assert_eq!(4, v1.start_frame(v1.start_time(4)));
+        assert_eq!(4, v1.end_frame(v1.end_time(4)));
+
+        let cfr = Timecodes::from_fps(24000.0 / 1001.0).unwrap();
+        assert_eq!(None, Timecodes::from_fps(0.0));
+        assert_eq!(None, Timecodes::from_fps(f64::NAN));
+        assert_eq!(24, cfr.frame_at(Timestamp::new(0, 0, 1, 1)));
+        assert!(matches!(Timecodes::parse("# timecode format v2\n0\n40\n30\n"), Err(ParseError::InvalidLine { line: 4, .. })));
+        assert!(matches!(Timecodes::parse("# timecode format v2\n0\n0\n"), Err(ParseError::InvalidLine { line: 3, .. })));
+        assert!(matches!(Timecodes::parse("0\n40\n"), Err(ParseError::UnsupportedFormat(_))));
+
+        let aegisub = Keyframes::parse("# keyframe format v1\nfps 0\n0\n50\n25\n").unwrap();
+        assert_eq!(vec![0, 25, 50], aegisub.frames);
+        let xvid = Keyframes::parse("# XviD 2pass stat file (core version 1.2.2)\n# comment\ni 1 2\np 1 2\nb 1 2\ni 1 2\n").unwrap();
+        assert_eq!(vec![0, 3], xvid.frames);
+        let x264 = Keyframes::parse("#options: 1920x1080\nin:0 out:0 type:I dur:2;\nin:2 out:1 type:P dur:2;\nin:1 out:2 type:i dur:2;\n").unwrap();
+        assert_eq!(vec![0, 1], x264.frames);
+        assert_eq!(Some(25), aegisub.nearest(27, 2));
+        assert_eq!(None, aegisub.nearest(30, 2));
+
+        let mut events = Events::new()
+            .add_dialogue(Dialogue::default()
+                .set_start(cfr.start_time(23))
+                .set_end(cfr.end_time(46)))
+            .create();
+        events.snap_to_keyframes(&aegisub, &cfr, 3);
+        let snapped = &events.get_dialogues()[1];
+        assert_eq!(Some(cfr.start_time(25)), snapped.get_start());
+        assert_eq!(Some(cfr.start_time(50)), snapped.get_end());
+        assert_eq!(49, cfr.end_frame(snapped.get_end().unwrap()));
+
+        // the end would snap to the keyframe the start snaps to.
+        let mut events = Events::new()
+            .add_dialogue(Dialogue::default()
+                .set_start(cfr.start_time(26))
+                .set_end(cfr.end_time(27)))
+            .create();
+        events.snap_to_keyframes(&aegisub, &cfr, 3);
+        let snapped = &events.get_dialogues()[1];
+        assert_eq!(Some(cfr.start_time(25)), snapped.get_start());
+        assert_eq!(Some(cfr.end_time(27)), snapped.get_end());
+        assert!(matches!(Timecodes::parse("# timecode format v2\n0\n"), Err(ParseError::InvalidLine { line: 1, .. })));
+    }
+
+    #[test]
+    fn test_srt_parser() {
+        use parser::SrtData;
+        let contents = "\u{feff}5\r\n00:00:01,500 --> 00:00:02,000\r\nFirst line\r\nSecond line\r\n\r\n\r\n3\r\n00:00:03.250 --> 00:00:04,000 X1:10 X2:20\r\nThird\r\n\r\nafter a blank line\r\n\r\n00:01:00,000 --> 00:01:01,000\r\nNo index, no trailing blank line";
+        let srt = SrtData::new().parse_srt(contents).unwrap();
+
+        assert_eq!(3, srt.len());
+        assert_eq!("5", srt[0].index);
+        assert_eq!("First line\\NSecond line", srt[0].text);
+        assert_eq!(Timestamp::new(0, 0, 3, 25), srt[1].start);
+        assert_eq!("Third\\N\\Nafter a blank line", srt[1].text);
+        assert_eq!("", srt[2].index);
+        assert_eq!(Timestamp::new(0, 1, 1, 0), srt[2].end);
+        assert_eq!("No index, no trailing blank line", srt[2].text);
+
+        let malformed = SrtData::new().parse_srt("1\n00:00:01,000 -> 00:00:02,000\nText\n");
+        assert!(matches!(malformed, Err(ParseError::BadTimestamp { line: 1, .. })));
+        let malformed = SrtData::new().parse_srt("1\n00:00:01,000 --> 00:00:xx,000\nText\n");
+        assert!(matches!(malformed, Err(ParseError::BadTimestamp { line: 2, .. })));
+        let malformed = SrtData::new().parse_srt("1\n00:00:01,000 --> 00:00:02,000\nHello\n\n2\n00:00:xx,000 --> 00:00:05,000\nWorld\n");
+        assert!(matches!(malformed, Err(ParseError::BadTimestamp { line: 6, .. })));
+
+        let lyrics = SrtData::new().parse_srt("1\n00:00:01,000 --> 00:00:02,000\nVerse\n\n\nThen --> go\n\nand -->\nback\n").unwrap();
+        assert_eq!(1, lyrics.len());
+        assert_eq!("Verse\\N\\NThen --> go\\N\\Nand -->\\Nback", lyrics[0].text);
+    }
+
+    #[test]
+    fn test_srt_export() {
+        let events = Events::new()
+            .add_dialogue(Dialogue::default()
+                .set_start(Timestamp::new(0, 0, 5, 0))
+                .set_end(Timestamp::new(1, 2, 3, 45))
+                .set_text(r"{\pos(10,10)\u1}Second{\r}\Nline"))
+            .add_dialogue(Dialogue::default()
+                .set_start(Timestamp::new(0, 0, 1, 50))
+                .set_end(Timestamp::new(0, 0, 4, 90))
+                .set_text(r"{\b1}First{\b0}\h!"))
+            .add_dialogue(Dialogue::default()
+                .set_kind(EventKind::Comment)
+                .set_start(Timestamp::new(0, 0, 1, 50))
+                .set_end(Timestamp::new(0, 0, 4, 90))
+                .set_text("Not exported"))
+            .add_dialogue(Dialogue::default()
+                .set_start(Timestamp::new(0, 0, 1, 50))
+                .set_end(Timestamp::new(0, 0, 4, 90))
+                .set_text(r"{\p1}m 0 0 l 10 10"))
+            .create();
+
+        let expected = "1\n00:00:01,500 --> 00:00:04,900\n<b>First</b>\u{a0}!\n\n2\n00:00:05,000 --> 01:02:03,450\n<u>Second</u>\nline\n\n";
+        assert_eq!(expected, events.to_srt());
+
+        let srt = parser::SrtData::new().parse_srt(expected).unwrap();
+        assert_eq!("{\\u1}Second{\\u0}\\Nline", srt[1].text);
+
+        let weights = TaggedText::parse(r"{\b400}1 < 2{\b700} heavy{\b100}");
+        assert_eq!("1 &lt; 2<b> heavy</b>", weights.to_html());
+    }
+
+    #[test]
+    fn test_srt_html_import() {
+        use parser::SrtData;
+        let contents = "1\n00:00:01,000 --> 00:00:02,000\n{\\an8}<i>Top <b>bold</b></i>\n<font color=\"#FF8000\">or<font color='red'>ange</font></font> 1 < 2 <br>\n\n2\n00:00:03,000 --> 00:00:04,000\n{\\a6}{note}<FONT COLOR=\"bad\">x</FONT>\n";
+        let srt = SrtData::new().parse_srt(contents).unwrap();
+
+        assert_eq!(
+            r"{\an8\i1}Top {\b1}bold{\b0\i0}\N{\c&H0080FF&}or{\c&H0000FF&}ange{\c&H0080FF&\c} 1 < 2 <br>",
+            srt[0].text,
+        );
+        assert_eq!(r#"{\an8}\{note\}<FONT COLOR="bad">x</FONT>"#, srt[1].text);
+
+        let path = SrtData::new().parse_srt("1\n00:00:01,000 --> 00:00:02,000\nC:\\New\\<i>{x}</i>\n").unwrap();
+        assert_eq!("C:\\\u{2060}New\\\u{2060}{\\i1}\\{x\\}{\\i0}", path[0].text);
+        assert_eq!("C:\\New\\{x}", TaggedText::parse(&path[0].text).plain_text());
+        assert_eq!(Some(Alignment::TopCenter), Alignment::from_ssa(6));
+        assert_eq!(10, Alignment::MiddleCenter.ssa());
+    }
+
+    #[test]
+    fn test_convert_srt() {
+        let mut options = ConvertOptions::default();
+        options.set_playres(1280, 720).set_primarycolour(AssColor::rgb(255, 255, 0));
+        options.style_mut().set_name("Main").set_fontsize(48.0);
+
+        let ass_file = AssFile::convert_srt("examples/rapgod.srt", &options).unwrap();
+        assert_eq!(Some(1280), ass_file.script.get_playresx());
+        assert_eq!(1, ass_file.v4.len());
+        assert_eq!(Some(48.0), ass_file.v4.get_style("Main").unwrap().get_fontsize());
+
+        let dialogues = ass_file.events.get_dialogues();
+        assert_eq!(Some(Timestamp::new(0, 0, 1, 50)), dialogues[0].get_start());
+        assert_eq!(Some("Main".to_string()), dialogues[0].get_style());
+
+        let saved = Parser::new().combine_components(&ass_file.components);
+        assert!(saved.contains("Style: Main,Arial,48,&H0000FFFF,"));
+        assert!(saved.contains("\nDialogue: 0,0:00:01.50,0:00:04.90,Main,,0,0,0,,Look, I was gonna go easy on\\Nyou"));
+        let reparsed = Parser::new().get_each_components(saved).unwrap();
+        assert_eq!(dialogues.len(), reparsed.events.get_dialogues().len());
+    }
+
+    #[test]
+    fn test_vtt() {
+        let contents = "\u{feff}WEBVTT - captions\r\n\r\nSTYLE\r\n::cue { color: white }\r\n\r\nNOTE\r\nfirst\r\nsecond\r\n\r\nintro\r\n00:00:01.000 --> 00:00:02.500 align:start position:10% size:50%\r\n<c.yellow.loud>Yellow <c.big>still</c> &amp; </c>back\r\nline &lt;2&gt;\r\n\r\n01:00.000 --> 01:02.000 line:-1\r\n<v.loud Ann>Hi</v>\r\n";
+        let vtt = WebVtt::parse(contents).unwrap();
+        assert_eq!("- captions", vtt.header);
+        assert_eq!(4, vtt.blocks.len());
+        assert_eq!(VttBlock::Style("::cue { color: white }".to_string()), vtt.blocks[0]);
+        assert_eq!(VttBlock::Note("first\nsecond".to_string()), vtt.blocks[1]);
+
+        let cues: Vec<&VttCue> = vtt.cues().collect();
+        assert_eq!("intro", cues[0].identifier);
+        assert_eq!(Timestamp::new(0, 1, 0, 0), cues[1].start);
+        assert_eq!(Alignment::BottomLeft, cues[0].settings.alignment());
+        assert_eq!([38, 0, 0], cues[0].settings.margins(384, 288));
+        assert_eq!("{\\c&H00FFFF&}Yellow still & {\\c}back\\Nline <2>", cues[0].ass_text());
+        assert_eq!(Some("Ann".to_string()), cues[1].voice());
+        assert_eq!(Alignment::BottomCenter, cues[1].settings.alignment());
+        assert_eq!(vtt, WebVtt::parse(&vtt.to_string()).unwrap());
+
+        let malformed = WebVtt::parse("WEBVTT\n\n00:01.000 --> 00:xx.000\nText\n");
+        assert!(matches!(malformed, Err(ParseError::BadTimestamp { line: 3, .. })));
+        let malformed = WebVtt::parse("1\n00:01.000 --> 00:02.000\nText\n");
+        assert!(matches!(malformed, Err(ParseError::UnsupportedFormat(_))));
+
+        let mut ass_file = AssFile::convert_vtt_contents(contents, &ConvertOptions::default()).unwrap();
+        let dialogues = ass_file.events.get_dialogues();
+        assert!(dialogues[0].is_comment());
+        assert_eq!(Some("first\\Nsecond".to_string()), dialogues[0].get_text());
+        assert_eq!(Some("38".to_string()), dialogues[1].get_marginl());
+        let expected = "WEBVTT\n\nNOTE\nfirst\nsecond\n\n00:00:01.000 --> 00:00:02.500 position:9.9% align:start\n<c.yellow>Yellow still &amp; </c>back\nline &lt;2&gt;\n\n00:01:00.000 --> 00:01:02.000\n<v Ann>Hi\n";
+        assert_eq!(expected, ass_file.to_vtt().to_string());
+
+        let dialogue = ass_file.components.events.dialogues.dialogues[2].clone().set_name("Ann <& Bo>");
+        ass_file.components.events.dialogues.dialogues[2] = dialogue;
+        assert!(ass_file.to_vtt().to_string().ends_with("\n<v Ann &lt;&amp; Bo&gt;>Hi\n"));
+
+        let contents = "WEBVTT\n\n00:01.000 --> 00:02.000\n{note} C:\\New <i>\\h</i>\n";
+        let cue = WebVtt::parse(contents).unwrap().cues().next().unwrap().clone();
+        assert_eq!("\\{note\\} C:\\\u{2060}New {\\i1}\\\u{2060}h{\\i0}", cue.ass_text());
+        let ass_file = AssFile::convert_vtt_contents(contents, &ConvertOptions::default()).unwrap();
+        assert!(ass_file.to_vtt().to_string().ends_with("\n{note} C:\\New <i>\\h</i>\n"));
+    }
+
+    #[test]
+    fn test_ssa_v4() {
+        let contents = [
+            "[Script Info]",
+            "ScriptType: v4.00",
+            "",
+            "[V4 Styles]",
+            "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, TertiaryColour, BackColour, Bold, Italic, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, AlphaLevel, Encoding",
+            "Style: Default,Tahoma,24,16777215,65535,65280,-2147483640,-1,0,1,1,2,6,30,30,30,0,0",
+            "",
+            "[Events]",
+            "Format: Marked, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text",
+            "Dialogue: Marked=0,0:00:01.00,0:00:02.00,Default,Bob,0000,0000,0000,,{\\a6}Hi",
+            "",
+        ].join("\n");
+        let parser = Parser::new();
+        let components = parser.get_each_components(contents.clone()).unwrap();
+        assert_eq!(Some("v4.00+".to_string()), components.script.get_scripttype());
+
+        let style = components.v4.get_style("Default").unwrap();
+        assert_eq!(Some(AssColor::rgb(0, 255, 0)), style.get_outlinecolour());
+        assert_eq!(Some(AssColor::rgba(8, 0, 0, 0x80)), style.get_backcolour());
+        assert_eq!(Some(Alignment::TopCenter), style.get_alignment());
+        assert_eq!(Some(100.0), style.get_scalex());
+        assert_eq!(None, style.get_field("AlphaLevel"));
+
+        let dialogue = &components.events.get_dialogues()[0];
+        assert_eq!(Some("0".to_string()), dialogue.get_layer());
+        assert_eq!(None, dialogue.get_field("Marked"));
+
+        let saved = parser.combine_components(&components);
+        assert!(saved.contains("[V4+ Styles]\nFormat: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour,"));
+        assert!(saved.contains("\nStyle: Default,Tahoma,24,&H00FFFFFF,&H0000FFFF,&H0000FF00,&H80000008,-1,0,0,0,100,100,0,0,1,1,2,8,30,30,30,0\n"));
+        assert!(saved.contains("\nDialogue: 0,0:00:01.00,0:00:02.00,Default,Bob,0000,0000,0000,,{\\a6}Hi\n"));
+
+        let ass_file = AssFile {
+            _ass_file: String::new(),
+            components,
+        };
+        assert_eq!(contents, ass_file.to_ssa());
+    }
+
+    #[test]
+    fn test_microdvd() {
+        let contents = "{1}{1}23.976\n{0}{48}{Y:b}{y:i}First|{c:$0000FF}red /line\n{100}{}/Second|{y:u}third\n\n{200}{250}{P:10,10}{f:Arial}{s:20}Plain|Two\n";
+        let sub = MicroDvd::parse(contents).unwrap();
+        assert_eq!(Some(23.976), sub.fps);
+        assert_eq!(3, sub.lines.len());
+        assert_eq!(199, sub.lines[1].end);
+        assert_eq!("{\\b1\\i1}First{\\i0}\\N{\\c&H0000FF&}red /line", sub.lines[0].ass_text());
+        assert_eq!("{\\i1}Second{\\i0}\\N{\\u1}third", sub.lines[1].ass_text());
+        assert_eq!("{\\fnArial\\fs20}Plain{\\fn\\fs}\\NTwo", sub.lines[2].ass_text());
+
+        let malformed = MicroDvd::parse("{1}{x}Text\n");
+        assert!(matches!(malformed, Err(ParseError::BadTimestamp { line: 1, .. })));
+
+        let timecodes = Timecodes::from_fps(23.976).unwrap();
+        let ass_file = AssFile::convert_microdvd_contents(contents, &timecodes, &ConvertOptions::default()).unwrap();
+        assert_eq!(3, ass_file.events.get_dialogues().len());
+        let expected = "{0}{48}{y:b,i}First|{y:b}{c:$0000FF}red /line\n{100}{199}{y:i}Second|{y:u}third\n{200}{250}Plain|Two\n";
+        assert_eq!(expected, ass_file.events.to_microdvd(&timecodes).to_string());
+    }
+
+    #[test]
+    fn test_ttml() {
+        let contents = r##"<?xml version="1.0" encoding="UTF-8"?>
+<!-- a comment -->
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter" xmlns:tts="http://www.w3.org/ns/ttml#styling"
+    ttp:frameRate="25" ttp:tickRate="10000000" tts:extent="768px 576px" xml:lang="en">
+  <head>
+    <styling>
+      <style xml:id="base" tts:fontFamily="'Tiresias', sans-serif" tts:fontSize="32px"/>
+      <style xml:id="boxed" style="base" tts:color="#FFFF00" tts:backgroundColor="#00000080" tts:textAlign="left"/>
+    </styling>
+    <layout>
+      <region xml:id="top" tts:origin="10% 5%" tts:extent="80% 90%" tts:displayAlign="before"/>
+    </layout>
+  </head>
+  <body>
+    <div begin="10s">
+      <p begin="00:00:01:12" end="00:00:03:00" style="boxed">Fish &amp; <span tts:color="red">chips</span>
+        <br/>  <span tts:fontWeight="bold">now</span></p>
+      <p begin="50000000t" dur="2s" region="top" style="base" tts:textAlign="center">On top</p>
+    </div>
+  </body>
+</tt>"##;
+        let ass_file = AssFile::convert_ttml_contents(contents, &ConvertOptions::default()).unwrap();
+        assert_eq!(3, ass_file.v4.len());
+        let boxed = ass_file.v4.get_style("boxed").unwrap();
+        assert_eq!(Some("Tiresias".to_string()), boxed.get_fontname());
+        assert_eq!(Some(16.0), boxed.get_fontsize());
+        assert_eq!(Some(AssColor::rgb(255, 255, 0)), boxed.get_primarycolour());
+        assert_eq!(Some(BorderStyle::OpaqueBox), boxed.get_borderstyle());
+        assert_eq!(Some(AssColor::rgba(0, 0, 0, 0x7f)), boxed.get_backcolour());
+        assert_eq!(Some(Alignment::BottomLeft), boxed.get_alignment());
+
+        let dialogues = ass_file.events.get_dialogues();
+        assert_eq!(Some(Timestamp::from_millis(11480)), dialogues[0].get_start());
+        assert_eq!(Some(Timestamp::from_millis(13000)), dialogues[0].get_end());
+        assert_eq!(Some("Fish & {\\c&H0000FF&}chips{\\c}\\N{\\b1}now{\\b0}".to_string()), dialogues[0].get_text());
+        assert_eq!(Some(Timestamp::from_millis(15000)), dialogues[1].get_start());
+        assert_eq!(Some(Timestamp::from_millis(17000)), dialogues[1].get_end());
+        assert_eq!(Some("{\\an8}On top".to_string()), dialogues[1].get_text());
+        assert_eq!(Some("38".to_string()), dialogues[1].get_marginl());
+        assert_eq!(Some("14".to_string()), dialogues[1].get_marginv());
+
+        let ttml = ass_file.to_ttml();
+        assert!(ttml.contains(r##"<style xml:id="boxed" tts:fontFamily="Tiresias" tts:fontSize="16px" tts:color="#FFFF00FF" tts:textAlign="left" tts:backgroundColor="#00000080"/>"##));
+        assert!(ttml.contains(r##"<p begin="00:00:11.480" end="00:00:13.000" region="r1" style="boxed">Fish &amp; <span tts:color="#FF0000FF">chips</span><br/><span tts:fontWeight="bold">now</span></p>"##));
+        let converted = AssFile::convert_ttml_contents(&ttml, &ConvertOptions::default()).unwrap();
+        assert_eq!(ttml, converted.to_ttml());
+        assert_eq!(dialogues, converted.events.get_dialogues());
+
+        let malformed = AssFile::convert_ttml_contents("<tt>\n<body>\n<p>Text</div>\n</tt>", &ConvertOptions::default());
+        assert!(matches!(malformed, Err(ParseError::InvalidLine { line: 3, .. })));
+    }
+
+    #[test]
+    fn test_lrc() {
+        let contents = "[ar:Someone]\n[offset:+500]\n[length:01:30]\n\n[00:10.50][00:40.00]<00:10.50>Na <00:11.00>na<00:12.25>\n[00:15.000]Plain line\n[00:20.00]\n[1:05]Last\n";
+        let lrc = Lrc::parse(contents).unwrap();
+        assert_eq!(Some("Someone"), lrc.get_metadata("AR"));
+        assert_eq!(500, lrc.offset());
+        assert_eq!(5, lrc.lines.len());
+        assert_eq!(Timestamp::new(0, 0, 40, 0), lrc.lines[3].start);
+        assert_eq!(Timestamp::new(0, 0, 15, 0), lrc.line_end(0));
+        assert_eq!(Timestamp::new(0, 0, 40, 0), lrc.line_end(2));
+        assert_eq!(Timestamp::new(0, 1, 30, 0), lrc.line_end(4));
+        assert_eq!("{\\k50}Na {\\k125}na", lrc.lines[0].ass_text(lrc.line_end(0)));
+
+        let malformed = Lrc::parse("[ti:Song]\n[00:1x.00]Text\n");
+        assert!(matches!(malformed, Err(ParseError::BadTimestamp { line: 2, .. })));
+
+        let ass_file = AssFile::convert_lrc_contents(contents, &ConvertOptions::default()).unwrap();
+        let dialogues = ass_file.events.get_dialogues();
+        assert_eq!(4, dialogues.len());
+        assert_eq!(Some(Timestamp::new(0, 0, 10, 0)), dialogues[0].get_start());
+        assert_eq!(Some(Timestamp::new(0, 0, 14, 50)), dialogues[0].get_end());
+        assert_eq!(Some("Plain line".to_string()), dialogues[1].get_text());
+        assert_eq!(Some(Timestamp::new(0, 0, 19, 50)), dialogues[1].get_end());
+
+        let expected = "[00:10.00]<00:10.00>Na <00:10.50>na<00:11.75>\n[00:14.50]Plain line\n[00:19.50]\n[00:39.50]<00:39.50>Na <00:40.00>na<00:41.25>\n[01:04.50]Last\n[01:29.50]\n";
+        assert_eq!(expected, ass_file.events.to_lrc().to_string());
+    }
 }
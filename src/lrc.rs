@@ -0,0 +1,202 @@
+use crate::parser::push_override;
+use crate::tags::push_plain;
+use crate::{KaraokeKind, ParseError, Segment, Tag, TaggedText, Timestamp};
+use std::fmt;
+use std::time::Duration;
+
+/// How long the last line of lyrics lasts when nothing gives its end.
+const LAST_LINE_DURATION: Duration = Duration::from_secs(5);
+
+/// The lyrics of a song in the LRC (`.lrc`) format.
+///
+/// Each line starts with the time it is sung at, `[mm:ss.xx]`, and in enhanced LRC files each
+/// word starts with its own time, `<mm:ss.xx>`. The tags at the top of the file, like
+/// `[ar:Artist]` or `[offset:+250]`, give information about the song.
+///
+/// ```rust
+/// use ass_parser::{Lrc, Timestamp};
+///
+/// let lrc = Lrc::parse("[ti:Song]\n[00:12.00]<00:12.00>Hello <00:12.50>world<00:13.20>\n").unwrap();
+/// assert_eq!(vec![("ti".to_string(), "Song".to_string())], lrc.metadata);
+/// assert_eq!(Timestamp::new(0, 0, 12, 0), lrc.lines[0].start);
+/// assert_eq!("{\\k50}Hello {\\k70}world", lrc.lines[0].ass_text(lrc.line_end(0)));
+/// assert_eq!("[ti:Song]\n[00:12.00]<00:12.00>Hello <00:12.50>world<00:13.20>\n", lrc.to_string());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Lrc {
+    /// The ID tags, like `ar` for the artist or `length` for the length of the song, with their
+    /// value.
+    pub metadata: Vec<(String, String)>,
+    /// The lines, ordered by their start time.
+    pub lines: Vec<LrcLine>,
+}
+
+/// A line of LRC lyrics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LrcLine {
+    /// The time the line is sung at.
+    pub start: Timestamp,
+    /// The text with the `<mm:ss.xx>` times of its words. An empty line marks the end of the
+    /// line before it.
+    pub text: String,
+}
+
+impl Lrc {
+    /// Parse the content of a `.lrc` file. A line with several times, like
+    /// `[00:12.00][01:05.00]Chorus`, is repeated at each of them. A `ParseError::BadTimestamp`
+    /// is returned for a time which is not `mm:ss.xx`. The `offset` tag is not applied to the
+    /// times, see `Lrc::offset`.
+    pub fn parse(contents: &str) -> Result<Lrc, ParseError> {
+        let mut lrc = Lrc::default();
+
+        for (i, line) in contents.trim_start_matches('\u{feff}').lines().enumerate() {
+            let mut rest = line.trim();
+            let mut starts = Vec::new();
+            while let Some((tag, after)) = rest.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+                let (key, value) = tag.split_once(':').unwrap_or((tag, ""));
+                if !key.is_empty() && key.trim().chars().all(|c| c.is_ascii_digit()) {
+                    starts.push(Timestamp::from_lrc(tag).map_err(|_| ParseError::BadTimestamp {
+                        line: i + 1,
+                        text: line.to_string(),
+                    })?);
+                } else if starts.is_empty() {
+                    lrc.metadata.push((key.trim().to_string(), value.trim().to_string()));
+                }
+                rest = after;
+            }
+            for start in starts {
+                lrc.lines.push(LrcLine {
+                    start,
+                    text: rest.trim().to_string(),
+                });
+            }
+        }
+        lrc.lines.sort_by_key(|line| line.start);
+        Ok(lrc)
+    }
+
+    /// The value of an ID tag.
+    pub fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The `offset` tag in milliseconds. A positive offset makes the lyrics appear sooner.
+    pub fn offset(&self) -> i64 {
+        self.get_metadata("offset")
+            .and_then(|offset| offset.trim_start_matches('+').parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// The time the line at `index` ends at: the start of the next line, the time ending its
+    /// last word, the `length` of the song, or 5 seconds after its start for the last line.
+    pub fn line_end(&self, index: usize) -> Timestamp {
+        let line = &self.lines[index];
+        self.lines[index + 1..].iter()
+            .map(|next| next.start)
+            .find(|start| *start > line.start)
+            .or_else(|| line.words().last().filter(|(_, word)| word.is_empty()).and_then(|(end, _)| *end))
+            .or_else(|| self.get_metadata("length").and_then(|length| Timestamp::from_lrc(length).ok()))
+            .filter(|end| *end > line.start)
+            .unwrap_or(line.start + LAST_LINE_DURATION)
+    }
+}
+
+impl LrcLine {
+    /// The words of the line with the time they start at. Text before the first `<mm:ss.xx>`
+    /// time has none, and a time at the end of the line, the end of the last word, is followed
+    /// by an empty word.
+    pub fn words(&self) -> Vec<(Option<Timestamp>, String)> {
+        let mut words = Vec::new();
+        let mut word = (None, String::new());
+        let mut rest = self.text.as_str();
+
+        while let Some(open) = rest.find('<') {
+            word.1.push_str(&rest[..open]);
+            let time = rest[open + 1..].split_once('>')
+                .and_then(|(time, after)| Some((Timestamp::from_lrc(time).ok()?, after)));
+            match time {
+                Some((time, after)) => {
+                    if word.0.is_some() || !word.1.is_empty() {
+                        words.push(word);
+                    }
+                    word = (Some(time), String::new());
+                    rest = after;
+                },
+                None => {
+                    word.1.push('<');
+                    rest = &rest[open + 1..];
+                },
+            }
+        }
+        word.1.push_str(rest);
+        words.push(word);
+        words
+    }
+
+    /// The text with a `\k` tag timing each word, when the line has word times. `end` is the
+    /// time the last word ends at when the line does not end with a time, see `Lrc::line_end`.
+    pub fn ass_text(&self, end: Timestamp) -> String {
+        let words = self.words();
+        if let [(None, text)] = &words[..] {
+            return text.clone();
+        }
+
+        let mut ass = String::new();
+        for (i, (start, word)) in words.iter().enumerate() {
+            let next = words.get(i + 1).and_then(|(next, _)| *next);
+            if word.is_empty() && next.is_none() {
+                break;
+            }
+            let duration = next.unwrap_or(end) - start.unwrap_or(self.start);
+            push_override(&mut ass, &Tag::Karaoke(KaraokeKind::Fill, (duration.as_millis() / 10) as u32).to_string());
+            ass.push_str(word);
+        }
+        ass
+    }
+}
+
+/// The text of a `Dialogue` starting at `start` as a line of enhanced LRC. Each karaoke syllable
+/// starts with its time and the line ends with the time the last one ends at. Lines of text are
+/// joined with spaces and the other override tags and the drawings are dropped.
+pub(crate) fn from_tagged_text(text: &TaggedText, start: Timestamp) -> String {
+    let drawings = text.drawing_segments();
+    let mut time = start;
+    let mut karaoke = false;
+    let mut line = String::new();
+
+    for (i, segment) in text.segments.iter().enumerate() {
+        match segment {
+            Segment::Override(tags) => for tag in tags {
+                if let Tag::Karaoke(_, duration) = tag {
+                    line.push_str(&format!("<{}>", time.to_lrc()));
+                    time += Duration::from_millis(u64::from(*duration) * 10);
+                    karaoke = true;
+                }
+            },
+            Segment::Text(_) if drawings.contains(&i) => {},
+            Segment::Text(text) => {
+                let mut plain = String::new();
+                push_plain(&mut plain, text);
+                line.push_str(&plain.replace('\n', " "));
+            },
+        }
+    }
+    if karaoke {
+        line.push_str(&format!("<{}>", time.to_lrc()));
+    }
+    line
+}
+
+impl fmt::Display for Lrc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (key, value) in &self.metadata {
+            writeln!(f, "[{}:{}]", key, value)?;
+        }
+        for line in &self.lines {
+            writeln!(f, "[{}]{}", line.start.to_lrc(), line.text)?;
+        }
+        Ok(())
+    }
+}
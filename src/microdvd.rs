@@ -0,0 +1,260 @@
+use crate::parser::push_override;
+use crate::tags::HtmlStyle;
+use crate::{AssColor, ParseError, Tag, TaggedText};
+use std::fmt;
+
+/// A MicroDVD (`.sub`) file, whose subtitles are timed in frames of the video.
+///
+/// Each line is `{start}{end}text`, the first and last frames the subtitle is displayed on,
+/// followed by its text. `|` separates the lines of the text and control codes like `{y:i}`
+/// format them: lowercase codes apply to one line of the text and uppercase codes to all of
+/// them. Some files start with a `{1}{1}23.976` line giving the framerate of the video.
+///
+/// ```rust
+/// use ass_parser::MicroDvd;
+///
+/// let sub = MicroDvd::parse("{1}{1}25\n{25}{75}{y:i}Hello|there\n").unwrap();
+/// assert_eq!(Some(25.0), sub.fps);
+/// assert_eq!(25, sub.lines[0].start);
+/// assert_eq!("{\\i1}Hello{\\i0}\\Nthere", sub.lines[0].ass_text());
+/// assert_eq!("{1}{1}25\n{25}{75}{y:i}Hello|there\n", sub.to_string());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MicroDvd {
+    /// The framerate written on the first line of the file, if there is one.
+    pub fps: Option<f64>,
+    pub lines: Vec<MicroDvdLine>,
+}
+
+/// A subtitle of a MicroDVD file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MicroDvdLine {
+    /// The first frame the subtitle is displayed on.
+    pub start: usize,
+    /// The last frame the subtitle is displayed on.
+    pub end: usize,
+    /// The text with its control codes, lines separated by `|`.
+    pub text: String,
+}
+
+impl MicroDvd {
+    /// Parse the content of a `.sub` file. A `ParseError::BadTimestamp` is returned for a line
+    /// which does not start with `{start}{end}`. An empty end frame, for a subtitle lasting until
+    /// the next one, is replaced by the frame before the next subtitle.
+    pub fn parse(contents: &str) -> Result<MicroDvd, ParseError> {
+        let mut sub = MicroDvd::default();
+        // the subtitles whose end frame is empty.
+        let mut open_ended = Vec::new();
+
+        for (i, line) in contents.trim_start_matches('\u{feff}').lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (start, end, text) = parse_frames(line).ok_or_else(|| ParseError::BadTimestamp {
+                line: i + 1,
+                text: line.to_string(),
+            })?;
+
+            let fps = text.trim().parse::<f64>().ok().filter(|fps| fps.is_finite() && *fps > 0.0);
+            if sub.lines.is_empty() && sub.fps.is_none() && start <= 1 && end.unwrap_or(0) <= 1 && fps.is_some() {
+                sub.fps = fps;
+                continue;
+            }
+            if end.is_none() {
+                open_ended.push(sub.lines.len());
+            }
+            sub.lines.push(MicroDvdLine {
+                start,
+                end: end.unwrap_or(start),
+                text: text.to_string(),
+            });
+        }
+
+        for i in open_ended {
+            if let Some(next) = sub.lines.get(i + 1).map(|next| next.start) {
+                sub.lines[i].end = next.saturating_sub(1).max(sub.lines[i].start);
+            }
+        }
+        Ok(sub)
+    }
+}
+
+/// Split `{25}{75}text` into its frames and text. The end frame is `None` when it is empty.
+fn parse_frames(line: &str) -> Option<(usize, Option<usize>, &str)> {
+    let (start, rest) = line.strip_prefix('{')?.split_once('}')?;
+    let (end, text) = rest.strip_prefix('{')?.split_once('}')?;
+    let end = match end.trim() {
+        "" => None,
+        end => Some(end.parse().ok()?),
+    };
+    Some((start.trim().parse().ok()?, end, text))
+}
+
+impl MicroDvdLine {
+    /// The text with `|` turned into `\N` and the control codes `{y:b}`, `{y:i}`, `{y:u}`,
+    /// `{y:s}`, `{c:$BBGGRR}`, `{f:font}` and `{s:size}` into override tags. A `/` at the start
+    /// of a line makes it italic. The other control codes, like `{P:x,y}`, are dropped.
+    pub fn ass_text(&self) -> String {
+        let mut whole = Vec::new();
+        let mut lines = Vec::new();
+
+        for line in self.text.split('|') {
+            let (codes, text) = split_codes(line);
+            let mut tags = Vec::new();
+            for (name, value) in codes {
+                let code_tags = control_tags(name.to_ascii_lowercase(), value);
+                if name.is_ascii_uppercase() {
+                    whole.extend(code_tags);
+                } else {
+                    tags.extend(code_tags);
+                }
+            }
+            let text = match text.strip_prefix('/') {
+                Some(text) => {
+                    tags.push(Tag::Italic(true));
+                    text
+                },
+                None => text,
+            };
+            lines.push((tags, text));
+        }
+
+        let mut ass = String::new();
+        for tag in &whole {
+            push_override(&mut ass, &tag.to_string());
+        }
+        let count = lines.len();
+        for (i, (tags, text)) in lines.into_iter().enumerate() {
+            for tag in &tags {
+                push_override(&mut ass, &tag.to_string());
+            }
+            ass.push_str(text);
+            if i + 1 < count {
+                // the formatting of a line stops at its end.
+                for tag in &tags {
+                    push_override(&mut ass, &reset_tag(tag, &whole).to_string());
+                }
+                ass.push_str("\\N");
+            }
+        }
+        ass
+    }
+}
+
+/// Split the control codes at the start of a line, with their name, from its text.
+fn split_codes(mut line: &str) -> (Vec<(char, &str)>, &str) {
+    let mut codes = Vec::new();
+    while let Some((code, rest)) = line.strip_prefix('{').and_then(|line| line.split_once('}')) {
+        let Some((name, value)) = code.split_once(':') else {
+            break;
+        };
+        let mut chars = name.trim().chars();
+        let (Some(name), None) = (chars.next(), chars.next()) else {
+            break;
+        };
+        codes.push((name, value));
+        line = rest;
+    }
+    (codes, line)
+}
+
+/// The override tags of a control code, `name` in lowercase.
+fn control_tags(name: char, value: &str) -> Vec<Tag> {
+    match name {
+        'y' => value.split(',')
+            .filter_map(|flag| match flag.trim().to_ascii_lowercase().as_str() {
+                "b" => Some(Tag::Bold(1)),
+                "i" => Some(Tag::Italic(true)),
+                "u" => Some(Tag::Underline(true)),
+                "s" => Some(Tag::StrikeOut(true)),
+                _ => None,
+            })
+            .collect(),
+        'c' => {
+            let hex = value.trim().trim_start_matches('$');
+            let colour = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6);
+            colour.map(|colour| {
+                let [_, blue, green, red] = colour.to_be_bytes();
+                Tag::Colour(1, AssColor::rgb(red, green, blue))
+            }).into_iter().collect()
+        },
+        'f' => vec![Tag::FontName(value.trim().to_string())],
+        's' => value.trim().parse().ok().map(Tag::FontSize).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The tag ending the formatting of `tag`, which goes back to the formatting of the whole
+/// subtitle if it has one.
+fn reset_tag(tag: &Tag, whole: &[Tag]) -> Tag {
+    let kind = std::mem::discriminant(tag);
+    if let Some(tag) = whole.iter().rev().find(|other| std::mem::discriminant(*other) == kind) {
+        return tag.clone();
+    }
+    match tag {
+        Tag::Bold(_) => Tag::Bold(0),
+        Tag::Italic(_) => Tag::Italic(false),
+        Tag::Underline(_) => Tag::Underline(false),
+        Tag::StrikeOut(_) => Tag::StrikeOut(false),
+        // `\c`, `\fn` and `\fs` without a value go back to the style.
+        Tag::Colour(..) => Tag::Unknown("c".to_string()),
+        Tag::FontName(_) => Tag::FontName(String::new()),
+        _ => Tag::Unknown("fs".to_string()),
+    }
+}
+
+/// The text of a `Dialogue` with the control codes of MicroDVD. Formatting applies to whole
+/// lines, so each line keeps the bold, italic, underline and colour of its first character and
+/// the other override tags are dropped.
+pub(crate) fn from_tagged_text(text: &TaggedText) -> String {
+    let lines = text.styled_lines();
+    let same_style = lines.windows(2).all(|pair| pair[0].1 == pair[1].1);
+    let mut sub = String::new();
+
+    if same_style {
+        if let Some((_, style)) = lines.first() {
+            sub.push_str(&control_codes(style, true));
+        }
+    }
+    for (i, (line, style)) in lines.iter().enumerate() {
+        if i > 0 {
+            sub.push('|');
+        }
+        if !same_style {
+            sub.push_str(&control_codes(style, false));
+        }
+        sub.push_str(line);
+    }
+    sub
+}
+
+/// The `{y:b,i}` and `{c:$BBGGRR}` codes of a line, or the `{Y:...}` and `{C:...}` codes of every
+/// line of the subtitle when `whole` is true.
+fn control_codes(style: &HtmlStyle, whole: bool) -> String {
+    let (y, c) = if whole { ('Y', 'C') } else { ('y', 'c') };
+    let flags: Vec<&str> = [(style.bold, "b"), (style.italic, "i"), (style.underline, "u")].into_iter()
+        .filter_map(|(on, flag)| on.then_some(flag))
+        .collect();
+
+    let mut codes = String::new();
+    if !flags.is_empty() {
+        codes.push_str(&format!("{{{}:{}}}", y, flags.join(",")));
+    }
+    if let Some(colour) = style.colour {
+        codes.push_str(&format!("{{{}:${:02X}{:02X}{:02X}}}", c, colour.blue, colour.green, colour.red));
+    }
+    codes
+}
+
+impl fmt::Display for MicroDvd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(fps) = self.fps {
+            writeln!(f, "{{1}}{{1}}{}", fps)?;
+        }
+        for line in &self.lines {
+            writeln!(f, "{{{}}}{{{}}}{}", line.start, line.end, line.text)?;
+        }
+        Ok(())
+    }
+}
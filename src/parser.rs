@@ -1,25 +1,34 @@
+use crate::{Alignment, AssColor, ParseError, Tag, Timestamp};
+
 #[derive(Debug, PartialEq)]
 pub struct SrtData {
     /// The index of the srt segment.
     pub index: String,
     /// the start timestamp.
-    pub start: String,
+    pub start: Timestamp,
     ///  the end timestamp.
-    pub end: String,
-    /// The caption in the srt segment.
+    pub end: Timestamp,
+    /// The caption in the srt segment. Lines of the caption are separated by `\N`, and the
+    /// `<b>`, `<i>`, `<u>`, `<s>` and `<font color>` HTML tags are turned into override tags.
     pub text: String,
 }
 
-type Segments = Vec<Vec<String>>;
+/// A block of lines separated from the others by blank lines, with the line number of its first
+/// line.
+type Segment<'a> = (usize, Vec<&'a str>);
 pub type SrtContent = Vec<SrtData>;
 
+const SRT_ARROW: &str = "-->";
+/// An invisible character, put after a backslash to keep it from starting an escape.
+pub(crate) const WORD_JOINER: char = '\u{2060}';
+
 
 impl SrtData {
     pub fn new() -> SrtData {
         SrtData {
             index: String::new(),
-            start: String::new(),
-            end:  String::new(),
+            start: Timestamp::ZERO,
+            end: Timestamp::ZERO,
             text:  String::new(),
         }
     }
@@ -27,74 +36,248 @@ impl SrtData {
 
 
 impl SrtData {
-    fn get_srt_segments(&self, srt_data: String) -> Segments {
-        let splitted = srt_data.lines();
+    fn get_srt_segments<'a>(&self, srt_data: &'a str) -> Vec<Segment<'a>> {
         let mut segments = Vec::new();
-        let mut current_buffer: Vec<String> = Vec::new();
+        let mut current_buffer: Vec<&str> = Vec::new();
+        let mut first_line = 1;
 
-        for line in splitted {
-            // println!("line: {}, {}", line, line.is_empty());
-            if line.is_empty() {
-                    segments.push(current_buffer);
+        for (i, line) in srt_data.lines().enumerate() {
+            if line.trim().is_empty() {
+                if !current_buffer.is_empty() {
+                    segments.push((first_line, current_buffer));
                     current_buffer = Vec::new();
+                }
             } else {
-                current_buffer.push(line.to_owned());
+                if current_buffer.is_empty() {
+                    first_line = i + 1;
+                }
+                current_buffer.push(line);
             }
         }
+        // the last caption is not always followed by a blank line.
+        if !current_buffer.is_empty() {
+            segments.push((first_line, current_buffer));
+        }
 
-        return segments;
+        segments
     }
 
-    fn parse_timestamps(&self, timestamps: String) -> [String; 2] {
-        let timestamp_splitted: Vec<&str> = timestamps.split(" --> ").collect();
-        let timestamps_str: Vec<String> = timestamp_splitted.iter().map(|s| s.to_string()).collect();
-        assert_eq!(2, timestamps_str.len());
+    /// Parse `00:00:01,500 --> 00:00:04,900`. Anything after the end timestamp, like the
+    /// `X1:... Y2:...` coordinates of some files, is ignored.
+    fn parse_timestamps(&self, line: usize, timestamps: &str) -> Result<[Timestamp; 2], ParseError> {
+        let bad_timestamp = || ParseError::BadTimestamp {
+            line,
+            text: timestamps.to_string(),
+        };
+        let (start, end) = timestamps.split_once(SRT_ARROW).ok_or_else(bad_timestamp)?;
+        let end = end.split_whitespace().next().ok_or_else(bad_timestamp)?;
 
-        let mut start_timestamp = timestamps_str[0].replace(",", ".");
-        let mut end_timestamp = timestamps_str[1].replace(",", ".");
+        // a few programs write a `.` instead of a `,` before the milliseconds.
+        let start_timestamp = Timestamp::from_srt(&start.trim().replace('.', ",")).map_err(|_| bad_timestamp())?;
+        let end_timestamp = Timestamp::from_srt(&end.replace('.', ",")).map_err(|_| bad_timestamp())?;
 
-        start_timestamp = start_timestamp.chars().skip(1).collect();
-        start_timestamp.pop();
-        end_timestamp = end_timestamp.chars().skip(1).collect();
-        end_timestamp.pop();
-
-        return [start_timestamp, end_timestamp];
+        Ok([start_timestamp, end_timestamp])
     }
 
-    fn get_srt(&self, srt_data: Segments) -> SrtContent {
-        let mut text = String::new();
+    fn get_srt(&self, srt_data: Vec<Segment>) -> Result<SrtContent, ParseError> {
+        // a line with a `-->` which does not start with a timestamp, like a lyric, is text.
+        let is_timing = |line: &str| line.contains(SRT_ARROW) && line.trim_start().starts_with(|c: char| c.is_ascii_digit());
         let mut srt_datas = Vec::<SrtData>::new();
-        for data in srt_data{
-            let mut srt_data = data.iter();
-            let index = srt_data.next().unwrap();
-            let timestamps = &self.parse_timestamps(srt_data.next().unwrap().to_string());
-            let start = &timestamps[0];
-            let end = &timestamps[1];
-
-            for srt_text in srt_data {
-                text.push_str(&(srt_text.to_owned() + " "));
-            }
-
-            let srt_data = SrtData {
-                index: index.to_string(),
-                start: start.to_string(),
-                end: end.to_string(),
-                text: text.clone(),
+        for (first_line, data) in srt_data {
+            // the index is optional, and does not have to follow the previous one.
+            let (index, timing) = match data[..] {
+                [timing, ..] if is_timing(timing) => (None, 0),
+                [index, timing, ..] if is_timing(timing) => (Some(index), 1),
+                _ => match srt_datas.last_mut() {
+                    // a caption which contains a blank line. Several blank lines are kept as a
+                    // single one.
+                    Some(previous) => {
+                        previous.text.push_str("\\N\\N");
+                        previous.text.push_str(&self.html_to_ass(&data.join("\n")));
+                        continue;
+                    },
+                    None => return Err(ParseError::BadTimestamp {
+                        line: first_line,
+                        text: data[0].to_string(),
+                    }),
+                },
             };
+            let [start, end] = self.parse_timestamps(first_line + timing, data[timing])?;
+
+            srt_datas.push(SrtData {
+                index: index.unwrap_or_default().trim().to_string(),
+                start,
+                end,
+                text: self.html_to_ass(&data[timing + 1..].join("\n")),
+            });
+        }
 
-            srt_datas.push(srt_data);
+        Ok(srt_datas)
+    }
+}
 
-            text.clear();
+impl SrtData {
+    /// Turn the HTML tags of a caption into override tags, eg. `<i>Hi</i>` into
+    /// `{\i1}Hi{\i0}`. The `{\an8}` tags some captions start with are kept, and the older
+    /// `{\a6}` form is turned into `{\an8}`. Anything else between `<` and `>` or `{` and `}` is
+    /// kept as text, see `push_text`.
+    fn html_to_ass(&self, text: &str) -> String {
+        let mut ass = String::new();
+        let mut colours: Vec<AssColor> = Vec::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find(['<', '{']) {
+            push_text(&mut ass, &rest[..start]);
+            rest = &rest[start..];
+            let close = if rest.starts_with('<') { '>' } else { '}' };
+            let Some(end) = rest.find(close) else {
+                break;
+            };
+            let (tag, after) = (&rest[..=end], &rest[end + 1..]);
+            rest = after;
+
+            let converted = if close == '}' {
+                legacy_alignment(tag)
+            } else {
+                html_tag(tag, &mut colours)
+            };
+            match converted {
+                Some(override_tag) => push_override(&mut ass, &override_tag),
+                None => push_text(&mut ass, tag),
+            }
         }
+        push_text(&mut ass, rest);
+        ass
+    }
+}
 
-        return srt_datas;
+/// The override tag for an HTML tag like `<b>`, `</i>` or `<font color="#ff0000">`.
+fn html_tag(tag: &str, colours: &mut Vec<AssColor>) -> Option<String> {
+    let inner = tag[1..tag.len() - 1].trim();
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(inner) => (true, inner.trim()),
+        None => (false, inner),
+    };
+    let name = inner.split_whitespace().next()?.to_ascii_lowercase();
+    let flag = if closing { 0 } else { 1 };
+
+    let tag = match name.as_str() {
+        "b" => Tag::Bold(flag),
+        "i" => Tag::Italic(!closing),
+        "u" => Tag::Underline(!closing),
+        "s" => Tag::StrikeOut(!closing),
+        // a `</font>` without a `<font>` before it is kept as text, like the `<font>` tags
+        // without a colour.
+        "font" if closing => {
+            colours.pop()?;
+            match colours.last() {
+                Some(colour) => Tag::Colour(1, *colour),
+                // `\c` without a value goes back to the colour of the style.
+                None => Tag::Unknown("c".to_string()),
+            }
+        },
+        "font" => {
+            let colour = font_colour(inner)?;
+            colours.push(colour);
+            Tag::Colour(1, colour)
+        },
+        _ => return None,
+    };
+    Some(tag.to_string())
+}
+
+/// The `color` attribute of a `<font>` tag, `#rrggbb` or one of the basic colour names.
+fn font_colour(font: &str) -> Option<AssColor> {
+    let start = font.to_ascii_lowercase().find("color")?;
+    let value = font[start + 5..].trim_start().strip_prefix('=')?.trim_start();
+    let value = value.trim_start_matches(['"', '\'']);
+    let value = &value[..value.find(['"', '\'', ' ', '>']).unwrap_or(value.len())];
+
+    let hex = match value.to_ascii_lowercase().as_str() {
+        "white" => "ffffff",
+        "black" => "000000",
+        "red" => "ff0000",
+        "lime" => "00ff00",
+        "green" => "008000",
+        "blue" => "0000ff",
+        "yellow" => "ffff00",
+        "cyan" | "aqua" => "00ffff",
+        "magenta" | "fuchsia" => "ff00ff",
+        "gray" | "grey" => "808080",
+        _ => value.trim_start_matches('#'),
+    };
+    if hex.len() != 6 {
+        return None;
+    }
+    let rgb = u32::from_str_radix(hex, 16).ok()?;
+    let [_, red, green, blue] = rgb.to_be_bytes();
+    Some(AssColor::rgb(red, green, blue))
+}
+
+/// `{\an8}` is kept as it is, `{\a6}` is turned into `{\an8}`.
+fn legacy_alignment(block: &str) -> Option<String> {
+    let inner = &block[1..block.len() - 1];
+    if let Some(value) = inner.strip_prefix("\\an") {
+        return Alignment::from_numpad(value.parse().ok()?).map(|alignment| Tag::Alignment(alignment).to_string());
+    }
+    let value = inner.strip_prefix("\\a")?;
+    Alignment::from_ssa(value.parse().ok()?).map(|alignment| Tag::Alignment(alignment).to_string())
+}
+
+/// Push an override tag, in the block which ends `ass` if there is one.
+pub(crate) fn push_override(ass: &mut String, tag: &str) {
+    if ass.ends_with('}') && !ass.ends_with("\\}") {
+        ass.pop();
+    } else {
+        ass.push('{');
+    }
+    ass.push_str(tag);
+    ass.push('}');
+}
+
+/// Push plain text, with its line breaks turned into `\N`. Braces are escaped as `\{` and `\}`,
+/// and a word joiner is put after a backslash which would start an escape like `\N` or be
+/// followed by an override block, so the text is displayed as it is.
+pub(crate) fn push_text(ass: &mut String, text: &str) {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => ass.push_str("\\N"),
+            '{' | '}' => {
+                ass.push('\\');
+                ass.push(c);
+            },
+            '\\' if matches!(chars.peek(), None | Some('N' | 'n' | 'h' | '{' | '}')) => {
+                ass.push('\\');
+                ass.push(WORD_JOINER);
+            },
+            _ => ass.push(c),
+        }
     }
 }
 
 impl SrtData {
-    pub fn parse_srt(&self, contents: String) -> SrtContent{
-        // let mut text = String::new();
+    /// Write captions in the SubRip format. `\N` in the text of a caption starts a new line.
+    pub fn write_srt(&self, captions: &[SrtData]) -> String {
+        let mut srt = String::new();
+        for caption in captions {
+            srt.push_str(&format!(
+                "{}\n{} {} {}\n{}\n\n",
+                caption.index,
+                caption.start.to_srt(),
+                SRT_ARROW,
+                caption.end.to_srt(),
+                caption.text.replace("\\N", "\n"),
+            ));
+        }
+        srt
+    }
+
+    /// Parse the content of a `.srt` file. A `ParseError::BadTimestamp` is returned for a timing
+    /// line which is not `HH:MM:SS,mmm --> HH:MM:SS,mmm`.
+    pub fn parse_srt(&self, contents: &str) -> Result<SrtContent, ParseError> {
+        let contents = contents.trim_start_matches('\u{feff}');
         let segments = self.get_srt_segments(contents);
-        return self.get_srt(segments);
+        self.get_srt(segments)
     }
 }
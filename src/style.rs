@@ -0,0 +1,130 @@
+/// Where a subtitle is placed on the screen.
+///
+/// `.ass` files use the layout of a numeric keypad: `1` is the bottom left corner, `5` the
+/// middle of the screen and `9` the top right corner.
+///
+/// ```rust
+/// use ass_parser::Alignment;
+///
+/// assert_eq!(Some(Alignment::TopCenter), Alignment::from_numpad(8));
+/// assert_eq!(2, Alignment::BottomCenter.numpad());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Alignment {
+    BottomLeft = 1,
+    #[default]
+    BottomCenter = 2,
+    BottomRight = 3,
+    MiddleLeft = 4,
+    MiddleCenter = 5,
+    MiddleRight = 6,
+    TopLeft = 7,
+    TopCenter = 8,
+    TopRight = 9,
+}
+
+impl Alignment {
+    /// Every alignment, in numpad order.
+    pub const ALL: [Alignment; 9] = [
+        Alignment::BottomLeft,
+        Alignment::BottomCenter,
+        Alignment::BottomRight,
+        Alignment::MiddleLeft,
+        Alignment::MiddleCenter,
+        Alignment::MiddleRight,
+        Alignment::TopLeft,
+        Alignment::TopCenter,
+        Alignment::TopRight,
+    ];
+
+    /// The alignment for a numpad value, `None` if it is not between `1` and `9`.
+    pub fn from_numpad(value: u8) -> Option<Alignment> {
+        Alignment::ALL.get(usize::from(value).checked_sub(1)?).copied()
+    }
+
+    /// The numpad value written in `.ass` files.
+    pub fn numpad(&self) -> u8 {
+        *self as u8
+    }
+
+    /// The alignment for a value of SubStation Alpha (`.ssa`) files and `\a` tags: `1` to `3`
+    /// at the bottom, add `4` for the top and `8` for the middle of the screen.
+    pub fn from_ssa(value: u8) -> Option<Alignment> {
+        let row = match value {
+            1..=3 => 0,
+            5..=7 => 6,
+            9..=11 => 3,
+            _ => return None,
+        };
+        Alignment::from_numpad(row + (value - 1) % 4 + 1)
+    }
+
+    /// The value used by SubStation Alpha (`.ssa`) files and `\a` tags.
+    pub fn ssa(&self) -> u8 {
+        let column = (self.numpad() - 1) % 3 + 1;
+        match self.numpad() {
+            1..=3 => column,
+            4..=6 => column + 8,
+            _ => column + 4,
+        }
+    }
+}
+
+/// How the border of a style is drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BorderStyle {
+    /// An outline around the text, plus a drop shadow.
+    #[default]
+    Outline = 1,
+    /// An opaque box behind the text.
+    OpaqueBox = 3,
+}
+
+impl BorderStyle {
+    /// The border style for the value written in `.ass` files, `None` if it is not `1` or `3`.
+    pub fn from_value(value: u8) -> Option<BorderStyle> {
+        match value {
+            1 => Some(BorderStyle::Outline),
+            3 => Some(BorderStyle::OpaqueBox),
+            _ => None,
+        }
+    }
+
+    /// The value written in `.ass` files.
+    pub fn value(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// How long lines are broken, the `WrapStyle` of the `[Script Info]` section.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WrapStyle {
+    /// Smart wrapping, lines are evenly broken and the top line is the wider one.
+    #[default]
+    SmartTop = 0,
+    /// Lines are broken at the end of the line, only `\N` breaks them otherwise.
+    EndOfLine = 1,
+    /// No word wrapping, both `\n` and `\N` break lines.
+    None = 2,
+    /// Smart wrapping, lines are evenly broken and the bottom line is the wider one.
+    SmartBottom = 3,
+}
+
+impl WrapStyle {
+    /// The wrap style for the value written in `.ass` files, `None` if it is not between `0` and
+    /// `3`.
+    pub fn from_value(value: u8) -> Option<WrapStyle> {
+        match value {
+            0 => Some(WrapStyle::SmartTop),
+            1 => Some(WrapStyle::EndOfLine),
+            2 => Some(WrapStyle::None),
+            3 => Some(WrapStyle::SmartBottom),
+            _ => None,
+        }
+    }
+
+    /// The value written in `.ass` files.
+    pub fn value(&self) -> u8 {
+        *self as u8
+    }
+}
@@ -0,0 +1,852 @@
+use crate::parser::WORD_JOINER;
+use crate::{vtt, Alignment, AssColor, WrapStyle};
+use std::fmt;
+
+/// The text of a `Dialogue`, split into plain text and override blocks.
+///
+/// Override blocks are the `{...}` parts of the text. Each one is parsed into typed `Tag`s, and
+/// whatever is not understood is kept as `Tag::Unknown` so it is written back as it was.
+/// Writing the text back may normalise the values of the tags, eg. `\c&H00ff&` becomes
+/// `\c&H0000FF&`.
+///
+/// ```rust
+/// use ass_parser::{AssColor, Segment, Tag, TaggedText};
+///
+/// let mut text = TaggedText::parse(r"{\c&H00ff&\pos(10,20)}Hi");
+/// assert_eq!(Some(&Tag::Position(10.0, 20.0)), text.tags().nth(1));
+///
+/// if let Some(Segment::Override(tags)) = text.segments.first_mut() {
+///     tags[0] = Tag::Colour(1, AssColor::rgb(0, 255, 0));
+/// }
+/// assert_eq!(r"{\c&H00FF00&\pos(10,20)}Hi", text.to_string());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TaggedText {
+    pub segments: Vec<Segment>,
+}
+
+/// A part of the text of a `Dialogue`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    /// Text displayed on screen, including the `\N`, `\n` and `\h` escapes.
+    Text(String),
+    /// An override block, written between braces.
+    Override(Vec<Tag>),
+}
+
+/// An override tag. Times are in milliseconds relative to the start of the line, unless stated
+/// otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    /// `\b`, `0` or `1`, or a font weight like `700`.
+    Bold(u32),
+    /// `\i`
+    Italic(bool),
+    /// `\u`
+    Underline(bool),
+    /// `\s`
+    StrikeOut(bool),
+    /// `\bord`, the width of the outline.
+    Border(f64),
+    /// `\shad`, the depth of the shadow.
+    Shadow(f64),
+    /// `\be`, blur the edges.
+    BlurEdges(f64),
+    /// `\blur`, gaussian blur of the edges.
+    Blur(f64),
+    /// `\fn`
+    FontName(String),
+    /// `\fs`
+    FontSize(f64),
+    /// `\fscx` [percent]
+    ScaleX(f64),
+    /// `\fscy` [percent]
+    ScaleY(f64),
+    /// `\fsp` [pixels]
+    Spacing(f64),
+    /// `\frx` [degrees]
+    RotationX(f64),
+    /// `\fry` [degrees]
+    RotationY(f64),
+    /// `\frz` or `\fr` [degrees]
+    RotationZ(f64),
+    /// `\fax`
+    ShearX(f64),
+    /// `\fay`
+    ShearY(f64),
+    /// `\c` or `\1c`...`\4c`: primary, secondary, outline or back colour. The alpha of the colour
+    /// is not part of the tag.
+    Colour(u8, AssColor),
+    /// `\alpha`, the transparency of every colour.
+    Alpha(u8),
+    /// `\1a`...`\4a`: the transparency of the primary, secondary, outline or back colour.
+    ColourAlpha(u8, u8),
+    /// `\an`
+    Alignment(Alignment),
+    /// `\q`
+    WrapStyle(WrapStyle),
+    /// `\r`, reset to the style of the line or to the named style.
+    Reset(String),
+    /// `\pos(x,y)`
+    Position(f64, f64),
+    /// `\move(x1,y1,x2,y2)` or `\move(x1,y1,x2,y2,t1,t2)`
+    Move {
+        from: (f64, f64),
+        to: (f64, f64),
+        times: Option<(i32, i32)>,
+    },
+    /// `\org(x,y)`, the origin of rotations.
+    Origin(f64, f64),
+    /// `\fad(in,out)`, the durations of the fade in and fade out.
+    Fade(u32, u32),
+    /// `\fade(a1,a2,a3,t1,t2,t3,t4)`
+    FadeComplex {
+        alphas: [u8; 3],
+        times: [i32; 4],
+    },
+    /// `\t([t1,t2,][accel,]tags)`, animate `tags` from `t1` to `t2`.
+    Transform {
+        times: Option<(i32, i32)>,
+        accel: Option<f64>,
+        tags: Vec<Tag>,
+    },
+    /// `\clip(...)` or `\iclip(...)` when `inverse` is true.
+    Clip {
+        inverse: bool,
+        shape: ClipShape,
+    },
+    /// `\k`, `\K`, `\kf` or `\ko` with a duration in centiseconds.
+    Karaoke(KaraokeKind, u32),
+    /// `\p`, the scale of the drawing which follows, `0` ends the drawing.
+    Drawing(u32),
+    /// Text at the start of an override block which is not a tag, used as a comment.
+    Comment(String),
+    /// Any other tag, without its leading backslash.
+    Unknown(String),
+}
+
+/// The area of a `\clip` tag.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipShape {
+    /// A rectangle from the top left to the bottom right corner.
+    Rectangle(f64, f64, f64, f64),
+    /// A vector drawing, with an optional scale like `\p`.
+    Drawing {
+        scale: Option<u32>,
+        commands: String,
+    },
+}
+
+/// The karaoke effect of a syllable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KaraokeKind {
+    /// `\k`, the syllable is highlighted at once.
+    Fill,
+    /// `\kf`, the highlight sweeps from left to right.
+    Sweep,
+    /// `\K`, the same as `\kf`.
+    SweepUpper,
+    /// `\ko`, the outline is highlighted at once.
+    Outline,
+}
+
+impl KaraokeKind {
+    fn name(&self) -> &'static str {
+        match self {
+            KaraokeKind::Fill => "k",
+            KaraokeKind::Sweep => "kf",
+            KaraokeKind::SweepUpper => "K",
+            KaraokeKind::Outline => "ko",
+        }
+    }
+}
+
+impl TaggedText {
+    /// Split the text of a `Dialogue` into plain text and override blocks.
+    /// A `{` without a closing `}`, or escaped as `\{`, is plain text.
+    pub fn parse(text: &str) -> TaggedText {
+        let mut segments = Vec::new();
+        let mut rest = text;
+
+        while !rest.is_empty() {
+            let block = rest.match_indices('{')
+                .map(|(start, _)| start)
+                .find(|start| !rest[..*start].ends_with('\\'))
+                .and_then(|start| rest[start..].find('}').map(|end| (start, start + end)));
+            match block {
+                Some((start, end)) => {
+                    if start > 0 {
+                        segments.push(Segment::Text(rest[..start].to_string()));
+                    }
+                    segments.push(Segment::Override(parse_block(&rest[start + 1..end])));
+                    rest = &rest[end + 1..];
+                },
+                None => {
+                    segments.push(Segment::Text(rest.to_string()));
+                    break;
+                },
+            }
+        }
+        TaggedText { segments }
+    }
+
+    /// The text displayed on screen, without the override blocks and the drawings of `\p`.
+    /// `\N` and `\n` become line breaks and `\h` a non-breaking space.
+    pub fn plain_text(&self) -> String {
+        let drawings = self.drawing_segments();
+        let mut plain = String::new();
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            if let Segment::Text(text) = segment {
+                if !drawings.contains(&i) {
+                    push_plain(&mut plain, text);
+                }
+            }
+        }
+        plain
+    }
+
+    /// The text displayed on screen with `\b`, `\i`, `\u` and `\c` written as `<b>`, `<i>`, `<u>`
+    /// and `<font color="#RRGGBB">` HTML tags, as used by SubRip files. Every other tag, like
+    /// positioning, and the drawings are dropped. A `<` in the text is escaped as `&lt;`.
+    ///
+    /// ```rust
+    /// use ass_parser::TaggedText;
+    ///
+    /// let text = TaggedText::parse(r"{\an8\b1}Bold{\b0} and {\i1\c&H0000FF&}red\Nitalic");
+    /// assert_eq!("<b>Bold</b> and <i><font color=\"#FF0000\">red\nitalic</font></i>", text.to_html());
+    /// ```
+    pub fn to_html(&self) -> String {
+        self.to_markup(Markup::SubRip)
+    }
+
+    /// The text displayed on screen with `\b`, `\i`, `\u` and `\c` written as the `<b>`, `<i>`,
+    /// `<u>` and `<c.yellow>` tags of WebVTT cues. Colours which are not one of the WebVTT colour
+    /// classes are dropped, and `&`, `<` and `>` are escaped.
+    pub(crate) fn to_vtt(&self) -> String {
+        self.to_markup(Markup::WebVtt)
+    }
+
+    fn to_markup(&self, markup: Markup) -> String {
+        let drawings = self.drawing_segments();
+        let mut html = String::new();
+        let mut style = HtmlStyle::default();
+        let mut open = HtmlStyle::default();
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Override(tags) => style.apply(tags),
+                Segment::Text(_) if drawings.contains(&i) => {},
+                Segment::Text(text) => {
+                    if style != open {
+                        open.close(&mut html, markup);
+                        style.open(&mut html, markup);
+                        open = style;
+                    }
+                    let mut plain = String::new();
+                    push_plain(&mut plain, text);
+                    match markup {
+                        Markup::SubRip => html.push_str(&plain.replace('<', "&lt;")),
+                        Markup::WebVtt => html.push_str(&vtt::escape(&plain)),
+                    }
+                },
+            }
+        }
+        open.close(&mut html, markup);
+        html
+    }
+
+    /// The lines displayed on screen, each with the formatting in effect at its first character.
+    /// Used by the formats which can only format whole lines.
+    pub(crate) fn styled_lines(&self) -> Vec<(String, HtmlStyle)> {
+        let drawings = self.drawing_segments();
+        let mut style = HtmlStyle::default();
+        let mut lines: Vec<(String, Option<HtmlStyle>)> = vec![(String::new(), None)];
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Override(tags) => style.apply(tags),
+                Segment::Text(_) if drawings.contains(&i) => {},
+                Segment::Text(text) => {
+                    let mut plain = String::new();
+                    push_plain(&mut plain, text);
+                    for (n, part) in plain.split('\n').enumerate() {
+                        if n > 0 {
+                            lines.push((String::new(), None));
+                        }
+                        if let Some((line, line_style)) = lines.last_mut() {
+                            if !part.is_empty() {
+                                line_style.get_or_insert(style);
+                            }
+                            line.push_str(part);
+                        }
+                    }
+                },
+            }
+        }
+        lines.into_iter().map(|(line, style)| (line, style.unwrap_or_default())).collect()
+    }
+
+    /// The indices of the text segments which are drawn, after a `\p` tag greater than `0`.
+    pub(crate) fn drawing_segments(&self) -> Vec<usize> {
+        let mut drawing = false;
+        let mut indices = Vec::new();
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Override(tags) => {
+                    for tag in tags {
+                        if let Tag::Drawing(scale) = tag {
+                            drawing = *scale > 0;
+                        }
+                    }
+                },
+                Segment::Text(_) if drawing => indices.push(i),
+                Segment::Text(_) => {},
+            }
+        }
+        indices
+    }
+
+    /// Keep the animations in place when the start of a line of `duration` milliseconds moves
+    /// `delay` milliseconds later: the times of `\move`, `\t`, `\fade` and the fade in of `\fad`
+    /// are relative to the start of the line, and the karaoke syllables sung before the new start
+    /// are shortened. Returns whether any tag changed.
+    pub(crate) fn delay_start(&mut self, delay: i32, duration: i32) -> bool {
+        // the centiseconds of karaoke which are still to be cut.
+        let mut karaoke_delay = (delay.max(0) as u32 + 5) / 10;
+        let mut changed = false;
+        for tag in self.tags_mut() {
+            match tag {
+                Tag::Karaoke(_, duration) => {
+                    let cut = (*duration).min(karaoke_delay);
+                    if cut == 0 {
+                        continue;
+                    }
+                    *duration -= cut;
+                    karaoke_delay -= cut;
+                },
+                Tag::Move { times, .. } | Tag::Transform { times, .. } => {
+                    let (start, end) = times.unwrap_or((0, duration));
+                    *times = Some((start - delay, end - delay));
+                },
+                Tag::Fade(fade_in, _) => {
+                    *fade_in = fade_in.saturating_sub(delay.max(0) as u32);
+                },
+                Tag::FadeComplex { times, .. } => {
+                    for time in times.iter_mut() {
+                        *time -= delay;
+                    }
+                },
+                _ => continue,
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    /// Multiply the times and durations of `\k`, `\t`, `\move`, `\fad` and `\fade` by `ratio`.
+    /// Karaoke durations are rounded so the total stays as close as possible to the scaled
+    /// total. Returns whether any tag changed.
+    pub(crate) fn scale_times(&mut self, ratio: f64) -> bool {
+        let scale = |time: &mut i32| *time = (*time as f64 * ratio).round() as i32;
+        let (mut karaoke, mut scaled_karaoke) = (0u64, 0u64);
+        let mut changed = false;
+
+        for tag in self.tags_mut() {
+            match tag {
+                Tag::Karaoke(_, duration) => {
+                    karaoke += u64::from(*duration);
+                    let total = (karaoke as f64 * ratio).round() as u64;
+                    *duration = (total - scaled_karaoke) as u32;
+                    scaled_karaoke = total;
+                },
+                Tag::Move { times: Some((start, end)), .. }
+                | Tag::Transform { times: Some((start, end)), .. } => {
+                    scale(start);
+                    scale(end);
+                },
+                Tag::Fade(fade_in, fade_out) => {
+                    *fade_in = (*fade_in as f64 * ratio).round() as u32;
+                    *fade_out = (*fade_out as f64 * ratio).round() as u32;
+                },
+                Tag::FadeComplex { times, .. } => times.iter_mut().for_each(scale),
+                _ => continue,
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    /// Iterate over the tags of every override block, in order.
+    /// The tags animated by a `\t` are not part of it.
+    pub fn tags(&self) -> impl Iterator<Item = &Tag> {
+        self.segments.iter().flat_map(|segment| match segment {
+            Segment::Override(tags) => tags.as_slice(),
+            Segment::Text(_) => &[],
+        })
+    }
+
+    /// Iterate over the tags of every override block mutably.
+    pub fn tags_mut(&mut self) -> impl Iterator<Item = &mut Tag> {
+        self.segments.iter_mut().flat_map(|segment| match segment {
+            Segment::Override(tags) => tags.as_mut_slice(),
+            Segment::Text(_) => &mut [],
+        })
+    }
+}
+
+impl From<&str> for TaggedText {
+    fn from(text: &str) -> TaggedText {
+        TaggedText::parse(text)
+    }
+}
+
+impl fmt::Display for TaggedText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for segment in &self.segments {
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Segment::Text(text) => write!(f, "{}", text),
+            Segment::Override(tags) => {
+                write!(f, "{{")?;
+                for tag in tags {
+                    write!(f, "{}", tag)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tag::Bold(weight) => write!(f, "\\b{}", weight),
+            Tag::Italic(value) => write!(f, "\\i{}", u8::from(*value)),
+            Tag::Underline(value) => write!(f, "\\u{}", u8::from(*value)),
+            Tag::StrikeOut(value) => write!(f, "\\s{}", u8::from(*value)),
+            Tag::Border(value) => write!(f, "\\bord{}", value),
+            Tag::Shadow(value) => write!(f, "\\shad{}", value),
+            Tag::BlurEdges(value) => write!(f, "\\be{}", value),
+            Tag::Blur(value) => write!(f, "\\blur{}", value),
+            Tag::FontName(name) => write!(f, "\\fn{}", name),
+            Tag::FontSize(value) => write!(f, "\\fs{}", value),
+            Tag::ScaleX(value) => write!(f, "\\fscx{}", value),
+            Tag::ScaleY(value) => write!(f, "\\fscy{}", value),
+            Tag::Spacing(value) => write!(f, "\\fsp{}", value),
+            Tag::RotationX(value) => write!(f, "\\frx{}", value),
+            Tag::RotationY(value) => write!(f, "\\fry{}", value),
+            Tag::RotationZ(value) => write!(f, "\\frz{}", value),
+            Tag::ShearX(value) => write!(f, "\\fax{}", value),
+            Tag::ShearY(value) => write!(f, "\\fay{}", value),
+            Tag::Colour(1, color) => write!(f, "\\c{}", color.to_tag()),
+            Tag::Colour(index, color) => write!(f, "\\{}c{}", index, color.to_tag()),
+            Tag::Alpha(alpha) => write!(f, "\\alpha&H{:02X}&", alpha),
+            Tag::ColourAlpha(index, alpha) => write!(f, "\\{}a&H{:02X}&", index, alpha),
+            Tag::Alignment(alignment) => write!(f, "\\an{}", alignment.numpad()),
+            Tag::WrapStyle(style) => write!(f, "\\q{}", style.value()),
+            Tag::Reset(style) => write!(f, "\\r{}", style),
+            Tag::Position(x, y) => write!(f, "\\pos({},{})", x, y),
+            Tag::Move { from, to, times } => {
+                write!(f, "\\move({},{},{},{}", from.0, from.1, to.0, to.1)?;
+                if let Some((start, end)) = times {
+                    write!(f, ",{},{}", start, end)?;
+                }
+                write!(f, ")")
+            },
+            Tag::Origin(x, y) => write!(f, "\\org({},{})", x, y),
+            Tag::Fade(fade_in, fade_out) => write!(f, "\\fad({},{})", fade_in, fade_out),
+            Tag::FadeComplex { alphas, times } => write!(
+                f,
+                "\\fade({},{},{},{},{},{},{})",
+                alphas[0], alphas[1], alphas[2], times[0], times[1], times[2], times[3],
+            ),
+            Tag::Transform { times, accel, tags } => {
+                write!(f, "\\t(")?;
+                if let Some((start, end)) = times {
+                    write!(f, "{},{},", start, end)?;
+                }
+                if let Some(accel) = accel {
+                    write!(f, "{},", accel)?;
+                }
+                for tag in tags {
+                    write!(f, "{}", tag)?;
+                }
+                write!(f, ")")
+            },
+            Tag::Clip { inverse, shape } => {
+                write!(f, "\\{}(", if *inverse { "iclip" } else { "clip" })?;
+                match shape {
+                    ClipShape::Rectangle(x1, y1, x2, y2) => write!(f, "{},{},{},{}", x1, y1, x2, y2)?,
+                    ClipShape::Drawing { scale: Some(scale), commands } => write!(f, "{},{}", scale, commands)?,
+                    ClipShape::Drawing { scale: None, commands } => write!(f, "{}", commands)?,
+                }
+                write!(f, ")")
+            },
+            Tag::Karaoke(kind, duration) => write!(f, "\\{}{}", kind.name(), duration),
+            Tag::Drawing(scale) => write!(f, "\\p{}", scale),
+            Tag::Comment(text) => write!(f, "{}", text),
+            Tag::Unknown(tag) => write!(f, "\\{}", tag),
+        }
+    }
+}
+
+/// The flavour of HTML tags written by `TaggedText::to_markup`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Markup {
+    /// `<font color="#RRGGBB">` colours.
+    SubRip,
+    /// `<c.yellow>` colours and escaped text.
+    WebVtt,
+}
+
+/// The formatting of the text which HTML tags can describe.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct HtmlStyle {
+    pub(crate) bold: bool,
+    pub(crate) italic: bool,
+    pub(crate) underline: bool,
+    pub(crate) colour: Option<AssColor>,
+}
+
+impl HtmlStyle {
+    fn apply(&mut self, tags: &[Tag]) {
+        for tag in tags {
+            match tag {
+                // `\b1`, or a font weight: only 700 and above are bold.
+                Tag::Bold(weight) => self.bold = *weight == 1 || *weight >= 700,
+                Tag::Italic(italic) => self.italic = *italic,
+                Tag::Underline(underline) => self.underline = *underline,
+                Tag::Colour(1, colour) => self.colour = Some(*colour),
+                // `\c` without a value goes back to the colour of the style.
+                Tag::Unknown(tag) if tag == "c" || tag == "1c" => self.colour = None,
+                Tag::Reset(_) => *self = HtmlStyle::default(),
+                _ => {},
+            }
+        }
+    }
+
+    fn open(&self, html: &mut String, markup: Markup) {
+        if self.bold {
+            html.push_str("<b>");
+        }
+        if self.italic {
+            html.push_str("<i>");
+        }
+        if self.underline {
+            html.push_str("<u>");
+        }
+        match (self.colour, markup) {
+            (Some(colour), Markup::SubRip) => {
+                html.push_str(&format!("<font color=\"#{:02X}{:02X}{:02X}\">", colour.red, colour.green, colour.blue));
+            },
+            (Some(colour), Markup::WebVtt) => {
+                if let Some(name) = vtt::colour_name(colour) {
+                    html.push_str(&format!("<c.{}>", name));
+                }
+            },
+            (None, _) => {},
+        }
+    }
+
+    fn close(&self, html: &mut String, markup: Markup) {
+        match (self.colour, markup) {
+            (Some(_), Markup::SubRip) => html.push_str("</font>"),
+            (Some(colour), Markup::WebVtt) if vtt::colour_name(colour).is_some() => html.push_str("</c>"),
+            _ => {},
+        }
+        if self.underline {
+            html.push_str("</u>");
+        }
+        if self.italic {
+            html.push_str("</i>");
+        }
+        if self.bold {
+            html.push_str("</b>");
+        }
+    }
+}
+
+/// Push `text` with its `\N`, `\n`, `\h`, `\{` and `\}` escapes replaced. See `parser::push_text`.
+pub(crate) fn push_plain(plain: &mut String, text: &str) {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let escaped = match (c, chars.peek()) {
+            ('\\', Some('N' | 'n')) => '\n',
+            ('\\', Some('h')) => '\u{a0}',
+            ('\\', Some(brace @ ('{' | '}'))) => *brace,
+            ('\\', Some(&WORD_JOINER)) => '\\',
+            _ => {
+                plain.push(c);
+                continue;
+            },
+        };
+        chars.next();
+        plain.push(escaped);
+    }
+}
+
+/// Parse the content of an override block, without its braces.
+fn parse_block(block: &str) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    let comment_end = block.find('\\').unwrap_or(block.len());
+    if comment_end > 0 {
+        tags.push(Tag::Comment(block[..comment_end].to_string()));
+    }
+
+    for body in split_tags(&block[comment_end..]) {
+        tags.push(parse_tag(body).unwrap_or_else(|| Tag::Unknown(body.to_string())));
+    }
+    tags
+}
+
+/// Split `\b1\t(\fs20)\i1` into `b1`, `t(\fs20)` and `i1`. Backslashes inside parentheses do not
+/// start a new tag.
+fn split_tags(block: &str) -> Vec<&str> {
+    let mut bodies = Vec::new();
+    let mut depth = 0;
+    let mut start = None;
+
+    for (i, c) in block.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = (depth - 1).max(0),
+            '\\' if depth == 0 => {
+                if let Some(start) = start {
+                    bodies.push(&block[start..i]);
+                }
+                start = Some(i + 1);
+            },
+            _ => {},
+        }
+    }
+    if let Some(start) = start {
+        bodies.push(&block[start..]);
+    }
+    bodies
+}
+
+/// Parse a tag without its leading backslash, `None` when it is not a known tag or its value is
+/// not valid.
+fn parse_tag(body: &str) -> Option<Tag> {
+    let tag = body.trim_end();
+
+    // longer names first, `\fscx` also starts with `\fs`.
+    if let Some(value) = tag.strip_prefix("fscx") {
+        return number(value).map(Tag::ScaleX);
+    }
+    if let Some(value) = tag.strip_prefix("fscy") {
+        return number(value).map(Tag::ScaleY);
+    }
+    if let Some(value) = tag.strip_prefix("fsp") {
+        return number(value).map(Tag::Spacing);
+    }
+    if let Some(value) = tag.strip_prefix("fs") {
+        return number(value).map(Tag::FontSize);
+    }
+    if let Some(value) = tag.strip_prefix("fn") {
+        return Some(Tag::FontName(value.to_string()));
+    }
+    if let Some(value) = tag.strip_prefix("frx") {
+        return number(value).map(Tag::RotationX);
+    }
+    if let Some(value) = tag.strip_prefix("fry") {
+        return number(value).map(Tag::RotationY);
+    }
+    if let Some(value) = tag.strip_prefix("frz").or_else(|| tag.strip_prefix("fr")) {
+        return number(value).map(Tag::RotationZ);
+    }
+    if let Some(value) = tag.strip_prefix("fax") {
+        return number(value).map(Tag::ShearX);
+    }
+    if let Some(value) = tag.strip_prefix("fay") {
+        return number(value).map(Tag::ShearY);
+    }
+    if let Some(value) = tag.strip_prefix("fade") {
+        let [a1, a2, a3, t1, t2, t3, t4] = arguments(value)?[..] else {
+            return None;
+        };
+        return Some(Tag::FadeComplex {
+            alphas: [a1.parse().ok()?, a2.parse().ok()?, a3.parse().ok()?],
+            times: [t1.parse().ok()?, t2.parse().ok()?, t3.parse().ok()?, t4.parse().ok()?],
+        });
+    }
+    if let Some(value) = tag.strip_prefix("fad") {
+        let [fade_in, fade_out] = arguments(value)?[..] else {
+            return None;
+        };
+        return Some(Tag::Fade(fade_in.parse().ok()?, fade_out.parse().ok()?));
+    }
+    if let Some(value) = tag.strip_prefix("bord") {
+        return number(value).map(Tag::Border);
+    }
+    if let Some(value) = tag.strip_prefix("blur") {
+        return number(value).map(Tag::Blur);
+    }
+    if let Some(value) = tag.strip_prefix("be") {
+        return number(value).map(Tag::BlurEdges);
+    }
+    if let Some(value) = tag.strip_prefix('b') {
+        return value.parse().ok().map(Tag::Bold);
+    }
+    if let Some(value) = tag.strip_prefix("shad") {
+        return number(value).map(Tag::Shadow);
+    }
+    if let Some(value) = tag.strip_prefix("iclip") {
+        return clip(value, true);
+    }
+    if let Some(value) = tag.strip_prefix("clip") {
+        return clip(value, false);
+    }
+    if let Some(value) = tag.strip_prefix('i') {
+        return flag(value).map(Tag::Italic);
+    }
+    if let Some(value) = tag.strip_prefix('u') {
+        return flag(value).map(Tag::Underline);
+    }
+    if let Some(value) = tag.strip_prefix('s') {
+        return flag(value).map(Tag::StrikeOut);
+    }
+    if let Some(value) = tag.strip_prefix("alpha") {
+        return alpha(value).map(Tag::Alpha);
+    }
+    if let Some(value) = tag.strip_prefix("an") {
+        return value.parse().ok().and_then(Alignment::from_numpad).map(Tag::Alignment);
+    }
+    if let Some(value) = tag.strip_prefix('c') {
+        return value.parse().ok().map(|color| Tag::Colour(1, color));
+    }
+    if let [index @ b'1'..=b'4', kind, ..] = tag.as_bytes() {
+        let index = index - b'0';
+        return match kind {
+            b'c' => tag[2..].parse().ok().map(|color| Tag::Colour(index, color)),
+            b'a' => alpha(&tag[2..]).map(|alpha| Tag::ColourAlpha(index, alpha)),
+            _ => None,
+        };
+    }
+    if let Some(value) = tag.strip_prefix('q') {
+        return value.parse().ok().and_then(WrapStyle::from_value).map(Tag::WrapStyle);
+    }
+    if let Some(value) = tag.strip_prefix('r') {
+        return Some(Tag::Reset(value.to_string()));
+    }
+    if let Some(value) = tag.strip_prefix("pos") {
+        let [x, y] = numbers(value)?[..] else {
+            return None;
+        };
+        return Some(Tag::Position(x, y));
+    }
+    if let Some(value) = tag.strip_prefix("move") {
+        return match numbers(value)?[..] {
+            [x1, y1, x2, y2] => Some(Tag::Move { from: (x1, y1), to: (x2, y2), times: None }),
+            [x1, y1, x2, y2, t1, t2] if t1.fract() == 0.0 && t2.fract() == 0.0 => Some(Tag::Move {
+                from: (x1, y1),
+                to: (x2, y2),
+                times: Some((t1 as i32, t2 as i32)),
+            }),
+            _ => None,
+        };
+    }
+    if let Some(value) = tag.strip_prefix("org") {
+        let [x, y] = numbers(value)?[..] else {
+            return None;
+        };
+        return Some(Tag::Origin(x, y));
+    }
+    if let Some(value) = tag.strip_prefix('t') {
+        return transform(value);
+    }
+    if let Some(value) = tag.strip_prefix("kf") {
+        return value.parse().ok().map(|duration| Tag::Karaoke(KaraokeKind::Sweep, duration));
+    }
+    if let Some(value) = tag.strip_prefix("ko") {
+        return value.parse().ok().map(|duration| Tag::Karaoke(KaraokeKind::Outline, duration));
+    }
+    if let Some(value) = tag.strip_prefix('k') {
+        return value.parse().ok().map(|duration| Tag::Karaoke(KaraokeKind::Fill, duration));
+    }
+    if let Some(value) = tag.strip_prefix('K') {
+        return value.parse().ok().map(|duration| Tag::Karaoke(KaraokeKind::SweepUpper, duration));
+    }
+    if let Some(value) = tag.strip_prefix('p') {
+        return value.parse().ok().map(Tag::Drawing);
+    }
+    None
+}
+
+fn number(value: &str) -> Option<f64> {
+    value.trim().parse().ok().filter(|value: &f64| value.is_finite())
+}
+
+/// `0` or `1`.
+fn flag(value: &str) -> Option<bool> {
+    match value {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    }
+}
+
+/// `&HAA&`, the alpha is the lowest byte of the value.
+fn alpha(value: &str) -> Option<u8> {
+    value.parse::<AssColor>().ok().map(|color| color.red)
+}
+
+/// The comma separated arguments between the parentheses of `(1,2)`.
+fn arguments(value: &str) -> Option<Vec<&str>> {
+    let inner = value.trim().strip_prefix('(')?.strip_suffix(')')?;
+    Some(inner.split(',').map(str::trim).collect())
+}
+
+fn numbers(value: &str) -> Option<Vec<f64>> {
+    arguments(value)?.into_iter().map(number).collect()
+}
+
+fn clip(value: &str, inverse: bool) -> Option<Tag> {
+    let args = arguments(value)?;
+    let shape = match args[..] {
+        [x1, y1, x2, y2] => ClipShape::Rectangle(number(x1)?, number(y1)?, number(x2)?, number(y2)?),
+        [scale, commands] => ClipShape::Drawing {
+            scale: Some(scale.parse().ok()?),
+            commands: commands.to_string(),
+        },
+        [commands] => ClipShape::Drawing {
+            scale: None,
+            commands: commands.to_string(),
+        },
+        _ => return None,
+    };
+    Some(Tag::Clip { inverse, shape })
+}
+
+/// `(t1,t2,accel,tags)`, every argument before the tags is optional.
+fn transform(value: &str) -> Option<Tag> {
+    let inner = value.trim().strip_prefix('(')?.strip_suffix(')')?;
+    let tags_start = inner.find('\\')?;
+    let args: Vec<&str> = inner[..tags_start].split(',')
+        .map(str::trim)
+        .filter(|arg| !arg.is_empty())
+        .collect();
+    let time = |arg: &str| arg.parse::<i32>().ok();
+
+    let (times, accel) = match args[..] {
+        [] => (None, None),
+        [accel] => (None, Some(number(accel)?)),
+        [t1, t2] => (Some((time(t1)?, time(t2)?)), None),
+        [t1, t2, accel] => (Some((time(t1)?, time(t2)?)), Some(number(accel)?)),
+        _ => return None,
+    };
+    Some(Tag::Transform {
+        times,
+        accel,
+        tags: parse_block(&inner[tags_start..]),
+    })
+}
@@ -0,0 +1,281 @@
+use crate::{ParseError, Timestamp};
+use std::fs;
+
+const TIMECODES_V1_HEADER: &str = "# timecode format v1";
+const TIMECODES_V2_HEADER: &str = "# timecode format v2";
+const AEGISUB_KEYFRAMES_HEADER: &str = "# keyframe format v1";
+const XVID_KEYFRAMES_HEADER: &str = "# XviD 2pass stat file";
+const X264_KEYFRAMES_HEADER: &str = "#options:";
+
+/// The time at which each frame of a video is displayed.
+///
+/// Load them from a Matroska timecode file (`# timecode format v1` or `v2`, as written by
+/// `mkvextract`) for variable framerate videos, or use `Timecodes::from_fps` for a constant
+/// framerate. Frames after the last one of the file are extrapolated.
+///
+/// A line starting at frame `n` is displayed from frame `n` onwards, so `start_time` gives a
+/// time between frame `n - 1` and frame `n`, which survives the rounding to centiseconds.
+///
+/// ```rust
+/// use ass_parser::{Timecodes, Timestamp};
+///
+/// let timecodes = Timecodes::parse("# timecode format v2\n0\n40\n80\n120\n").unwrap();
+/// assert_eq!(Timestamp::from_millis(60), timecodes.start_time(2));
+/// assert_eq!(2, timecodes.start_frame(Timestamp::from_millis(60)));
+/// assert_eq!(Timestamp::from_millis(100), timecodes.end_time(2));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timecodes {
+    /// The time of each frame in milliseconds.
+    frames: Vec<f64>,
+    /// The duration of the frames after the last one, in milliseconds.
+    frame_duration: f64,
+}
+
+/// The frames of a video which are keyframes, usually scene changes.
+///
+/// Load them from an Aegisub keyframe file, an XviD pass 1 stats file or an x264 pass 1 stats
+/// file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Keyframes {
+    /// The keyframes, in ascending order.
+    pub frames: Vec<usize>,
+}
+
+impl Timecodes {
+    /// Timecodes of a video with a constant framerate. Returns `None` if `fps` is not a finite
+    /// number above zero.
+    pub fn from_fps(fps: f64) -> Option<Timecodes> {
+        (fps.is_finite() && fps > 0.0).then(|| Timecodes {
+            frames: vec![0.0],
+            frame_duration: 1000.0 / fps,
+        })
+    }
+
+    /// Load a Matroska timecode file.
+    pub fn from_file(filename: &str) -> Result<Timecodes, ParseError> {
+        Timecodes::parse(&fs::read_to_string(filename)?)
+    }
+
+    /// Parse the content of a Matroska timecode file, v1 or v2.
+    pub fn parse(contents: &str) -> Result<Timecodes, ParseError> {
+        let mut lines = contents.trim_start_matches('\u{feff}').lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()));
+        let header = lines.next().map_or("", |(_, line)| line);
+        let lines = lines.filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        if header.eq_ignore_ascii_case(TIMECODES_V2_HEADER) {
+            parse_v2(lines)
+        } else if header.eq_ignore_ascii_case(TIMECODES_V1_HEADER) {
+            parse_v1(lines)
+        } else {
+            Err(ParseError::UnsupportedFormat(header.to_string()))
+        }
+    }
+
+    /// The time at which `frame` is displayed, in milliseconds.
+    pub fn frame_millis(&self, frame: usize) -> f64 {
+        match self.frames.get(frame) {
+            Some(millis) => *millis,
+            None => {
+                let last = self.frames.len() - 1;
+                self.frames[last] + (frame - last) as f64 * self.frame_duration
+            },
+        }
+    }
+
+    /// The first frame displayed at or after `millis`.
+    fn first_frame_from(&self, millis: f64) -> usize {
+        let frame = self.frames.partition_point(|time| *time < millis);
+        if frame < self.frames.len() {
+            return frame;
+        }
+        let last = self.frames.len() - 1;
+        last.saturating_add(((millis - self.frames[last]) / self.frame_duration).ceil() as usize)
+    }
+
+    /// The frame displayed at `time`.
+    pub fn frame_at(&self, time: Timestamp) -> usize {
+        let millis = time.as_millis() as f64;
+        let frame = self.first_frame_from(millis);
+        if frame > 0 && self.frame_millis(frame) > millis {
+            frame - 1
+        } else {
+            frame
+        }
+    }
+
+    /// The start time of a line whose first frame is `frame`.
+    pub fn start_time(&self, frame: usize) -> Timestamp {
+        if frame == 0 {
+            return Timestamp::ZERO;
+        }
+        let middle = (self.frame_millis(frame - 1) + self.frame_millis(frame)) / 2.0;
+        Timestamp::from_millis(middle.round() as u64)
+    }
+
+    /// The end time of a line whose last frame is `frame`.
+    pub fn end_time(&self, frame: usize) -> Timestamp {
+        self.start_time(frame + 1)
+    }
+
+    /// The first frame of a line starting at `time`.
+    pub fn start_frame(&self, time: Timestamp) -> usize {
+        self.first_frame_from(time.as_millis() as f64)
+    }
+
+    /// The last frame of a line ending at `time`. A line ending before its first frame is
+    /// displayed gives the frame before it.
+    pub fn end_frame(&self, time: Timestamp) -> usize {
+        self.first_frame_from(time.as_millis() as f64).saturating_sub(1)
+    }
+}
+
+/// One time in milliseconds per line, each later than the one before.
+fn parse_v2<'a>(lines: impl Iterator<Item = (usize, &'a str)>) -> Result<Timecodes, ParseError> {
+    let mut frames: Vec<f64> = Vec::new();
+    for (line, text) in lines {
+        let time = text.parse::<f64>().ok()
+            .filter(|time| time.is_finite() && !frames.last().is_some_and(|last| time <= last))
+            .ok_or_else(|| invalid_line(line, text))?;
+        frames.push(time);
+    }
+
+    let frame_duration = match frames[..] {
+        // at least two times are needed for the duration of the frames after the last one.
+        [] | [_] => return Err(invalid_line(1, TIMECODES_V2_HEADER)),
+        [first, .., last] => (last - first) / (frames.len() - 1) as f64,
+    };
+    Ok(Timecodes { frames, frame_duration })
+}
+
+/// `Assume <fps>` followed by `first frame,last frame,fps` ranges.
+fn parse_v1<'a>(mut lines: impl Iterator<Item = (usize, &'a str)>) -> Result<Timecodes, ParseError> {
+    let (line, text) = lines.next().ok_or_else(|| invalid_line(1, TIMECODES_V1_HEADER))?;
+    let assume = text.get(..6)
+        .filter(|key| key.eq_ignore_ascii_case("assume"))
+        .and_then(|_| text[6..].trim().parse::<f64>().ok())
+        .filter(|fps| fps.is_finite() && *fps > 0.0)
+        .ok_or_else(|| invalid_line(line, text))?;
+
+    let mut ranges = Vec::new();
+    for (line, text) in lines {
+        let values: Vec<&str> = text.split(',').map(str::trim).collect();
+        let range = match values[..] {
+            [first, last, fps] => first.parse::<usize>().ok().zip(last.parse::<usize>().ok())
+                .zip(fps.parse::<f64>().ok())
+                .filter(|((first, last), fps)| first <= last && fps.is_finite() && *fps > 0.0),
+            _ => None,
+        };
+        ranges.push(range.ok_or_else(|| invalid_line(line, text))?);
+    }
+
+    let count = ranges.iter().map(|((_, last), _)| last + 1).max().unwrap_or(0);
+    let mut frames = Vec::with_capacity(count + 1);
+    let mut time = 0.0;
+    for frame in 0..=count {
+        frames.push(time);
+        let fps = ranges.iter()
+            .find(|((first, last), _)| (*first..=*last).contains(&frame))
+            .map_or(assume, |(_, fps)| *fps);
+        time += 1000.0 / fps;
+    }
+    Ok(Timecodes {
+        frames,
+        frame_duration: 1000.0 / assume,
+    })
+}
+
+impl Keyframes {
+    /// Load an Aegisub keyframe file, an XviD pass 1 stats file or an x264 pass 1 stats file.
+    pub fn from_file(filename: &str) -> Result<Keyframes, ParseError> {
+        Keyframes::parse(&fs::read_to_string(filename)?)
+    }
+
+    /// Parse the content of a keyframe file, the format is found from its first line.
+    pub fn parse(contents: &str) -> Result<Keyframes, ParseError> {
+        let contents = contents.trim_start_matches('\u{feff}');
+        let header = contents.lines().next().unwrap_or("").trim();
+        let lines = contents.lines().enumerate().skip(1).map(|(i, line)| (i + 1, line.trim()));
+
+        let mut frames = if header.eq_ignore_ascii_case(AEGISUB_KEYFRAMES_HEADER) {
+            parse_aegisub(lines)?
+        } else if header.starts_with(XVID_KEYFRAMES_HEADER) {
+            parse_xvid(lines)
+        } else if header.starts_with(X264_KEYFRAMES_HEADER) {
+            parse_x264(lines)?
+        } else {
+            return Err(ParseError::UnsupportedFormat(header.to_string()));
+        };
+        frames.sort_unstable();
+        frames.dedup();
+        Ok(Keyframes { frames })
+    }
+
+    /// The keyframe closest to `frame`, if it is at most `threshold` frames away.
+    pub fn nearest(&self, frame: usize, threshold: usize) -> Option<usize> {
+        let after = self.frames.partition_point(|keyframe| *keyframe < frame);
+        let candidates = [after.checked_sub(1), Some(after)];
+        candidates.into_iter()
+            .flatten()
+            .filter_map(|i| self.frames.get(i).copied())
+            .filter(|keyframe| keyframe.abs_diff(frame) <= threshold)
+            .min_by_key(|keyframe| keyframe.abs_diff(frame))
+    }
+}
+
+/// `fps <fps>` followed by one keyframe per line.
+fn parse_aegisub<'a>(lines: impl Iterator<Item = (usize, &'a str)>) -> Result<Vec<usize>, ParseError> {
+    let mut frames = Vec::new();
+    for (line, text) in lines {
+        if text.is_empty() || text.starts_with('#') || text.to_ascii_lowercase().starts_with("fps") {
+            continue;
+        }
+        frames.push(text.parse().map_err(|_| invalid_line(line, text))?);
+    }
+    Ok(frames)
+}
+
+/// One line per frame, starting with its type. `i` frames are keyframes.
+fn parse_xvid<'a>(lines: impl Iterator<Item = (usize, &'a str)>) -> Vec<usize> {
+    let mut frames = Vec::new();
+    let mut frame = 0;
+    for (_, text) in lines {
+        match text.chars().next().map(|c| c.to_ascii_lowercase()) {
+            Some('i') => {
+                frames.push(frame);
+                frame += 1;
+            },
+            Some('p' | 'b' | 's') => frame += 1,
+            _ => {},
+        }
+    }
+    frames
+}
+
+/// One line per frame, `in:<frame> out:<frame> type:<type> ...`. `I` and `i` frames are
+/// keyframes.
+fn parse_x264<'a>(lines: impl Iterator<Item = (usize, &'a str)>) -> Result<Vec<usize>, ParseError> {
+    let mut frames = Vec::new();
+    for (line, text) in lines {
+        let mut fields = text.split_whitespace();
+        let frame = fields.clone().find_map(|field| field.strip_prefix("in:"));
+        let kind = fields.find_map(|field| field.strip_prefix("type:"));
+        match (frame, kind) {
+            (Some(frame), Some("I" | "i")) => {
+                frames.push(frame.trim_end_matches(';').parse().map_err(|_| invalid_line(line, text))?);
+            },
+            (None, Some(_)) => return Err(invalid_line(line, text)),
+            _ => {},
+        }
+    }
+    Ok(frames)
+}
+
+fn invalid_line(line: usize, text: &str) -> ParseError {
+    ParseError::InvalidLine {
+        line,
+        text: text.to_string(),
+    }
+}
@@ -0,0 +1,253 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A start or end time of an event, with the centisecond precision used by `.ass` files.
+///
+/// Timestamps are written in the `H:MM:SS.cc` format, ie. `0:01:02.50`.
+///
+/// ```rust
+/// use ass_parser::Timestamp;
+/// use std::time::Duration;
+///
+/// let start: Timestamp = "0:01:02.50".parse().unwrap();
+/// let end = start + Duration::from_millis(1500);
+///
+/// assert_eq!("0:01:04.00", end.to_string());
+/// assert_eq!(start, Timestamp::from_srt("00:01:02,500").unwrap());
+/// assert!(start < end);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    centiseconds: u64,
+}
+
+/// The error returned when a timestamp is not in the expected format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidTimestamp {
+    /// The text which could not be parsed.
+    pub text: String,
+}
+
+impl fmt::Display for InvalidTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid timestamp: {}", self.text)
+    }
+}
+
+impl std::error::Error for InvalidTimestamp {}
+
+impl Timestamp {
+    /// The start of the video.
+    pub const ZERO: Timestamp = Timestamp { centiseconds: 0 };
+
+    /// Create a timestamp from hours, minutes, seconds and hundredths of a second.
+    pub fn new(hours: u64, minutes: u64, seconds: u64, centiseconds: u64) -> Timestamp {
+        Timestamp {
+            centiseconds: ((hours * 60 + minutes) * 60 + seconds) * 100 + centiseconds,
+        }
+    }
+
+    /// Create a timestamp from a number of hundredths of a second.
+    pub fn from_centiseconds(centiseconds: u64) -> Timestamp {
+        Timestamp { centiseconds }
+    }
+
+    /// Create a timestamp from a number of milliseconds, rounded to the nearest centisecond.
+    pub fn from_millis(millis: u64) -> Timestamp {
+        Timestamp {
+            centiseconds: (millis + 5) / 10,
+        }
+    }
+
+    /// The number of hundredths of a second since the start of the video.
+    pub fn as_centiseconds(&self) -> u64 {
+        self.centiseconds
+    }
+
+    /// The number of milliseconds since the start of the video.
+    pub fn as_millis(&self) -> u64 {
+        self.centiseconds * 10
+    }
+
+    /// Multiply the time since the start of the video by `ratio`, rounded to the nearest
+    /// centisecond.
+    pub fn scale(&self, ratio: f64) -> Timestamp {
+        Timestamp {
+            centiseconds: (self.centiseconds as f64 * ratio).round().max(0.0) as u64,
+        }
+    }
+
+    /// Write the timestamp in the `HH:MM:SS,mmm` format of SubRip files.
+    pub fn to_srt(&self) -> String {
+        let seconds = self.centiseconds / 100;
+        format!(
+            "{:02}:{:02}:{:02},{:03}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+            self.centiseconds % 100 * 10,
+        )
+    }
+
+    /// Parse a SubRip timestamp in the `HH:MM:SS,mmm` format.
+    /// Milliseconds are rounded to the nearest centisecond.
+    pub fn from_srt(text: &str) -> Result<Timestamp, InvalidTimestamp> {
+        let (clock, fraction) = text.trim().split_once(',').ok_or_else(|| invalid(text))?;
+        parse_millis(clock, fraction)
+            .map(Timestamp::from_millis)
+            .ok_or_else(|| invalid(text))
+    }
+
+    /// Write the timestamp in the `HH:MM:SS.mmm` format of WebVTT files.
+    pub fn to_vtt(&self) -> String {
+        self.to_srt().replace(',', ".")
+    }
+
+    /// Parse a WebVTT timestamp in the `HH:MM:SS.mmm` or `MM:SS.mmm` format.
+    /// Milliseconds are rounded to the nearest centisecond.
+    pub fn from_vtt(text: &str) -> Result<Timestamp, InvalidTimestamp> {
+        let (clock, fraction) = text.trim().split_once('.').ok_or_else(|| invalid(text))?;
+        let clock = match clock.matches(':').count() {
+            1 => format!("0:{}", clock),
+            _ => clock.to_string(),
+        };
+        parse_millis(&clock, fraction)
+            .map(Timestamp::from_millis)
+            .ok_or_else(|| invalid(text))
+    }
+
+    /// Write the timestamp in the `MM:SS.cc` format of LRC lyrics, the minutes going past `59`.
+    pub fn to_lrc(&self) -> String {
+        let seconds = self.centiseconds / 100;
+        format!("{:02}:{:02}.{:02}", seconds / 60, seconds % 60, self.centiseconds % 100)
+    }
+
+    /// Parse an LRC timestamp in the `MM:SS.cc` format, or `MM:SS` and `MM:SS.mmm`.
+    /// Milliseconds are rounded to the nearest centisecond.
+    pub fn from_lrc(text: &str) -> Result<Timestamp, InvalidTimestamp> {
+        let (clock, fraction) = text.trim().split_once('.').unwrap_or((text.trim(), "0"));
+        let (minutes, seconds) = clock.split_once(':').ok_or_else(|| invalid(text))?;
+        let minutes = parse_number(minutes, 3).ok_or_else(|| invalid(text))?;
+        parse_millis(&format!("{}:{}:{}", minutes / 60, minutes % 60, seconds), fraction)
+            .map(Timestamp::from_millis)
+            .ok_or_else(|| invalid(text))
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = InvalidTimestamp;
+
+    /// Parse a timestamp in the `H:MM:SS.cc` format. A third decimal is rounded to the nearest
+    /// centisecond.
+    fn from_str(text: &str) -> Result<Timestamp, InvalidTimestamp> {
+        let (clock, fraction) = text.trim().split_once('.').unwrap_or((text.trim(), "0"));
+        parse_millis(clock, fraction)
+            .map(Timestamp::from_millis)
+            .ok_or_else(|| invalid(text))
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let seconds = self.centiseconds / 100;
+        write!(
+            f,
+            "{}:{:02}:{:02}.{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+            self.centiseconds % 100,
+        )
+    }
+}
+
+impl From<Duration> for Timestamp {
+    /// Convert a duration since the start of the video, rounded to the nearest centisecond.
+    fn from(duration: Duration) -> Timestamp {
+        Timestamp {
+            centiseconds: duration_centiseconds(duration),
+        }
+    }
+}
+
+impl From<Timestamp> for Duration {
+    fn from(timestamp: Timestamp) -> Duration {
+        Duration::from_millis(timestamp.as_millis())
+    }
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, duration: Duration) -> Timestamp {
+        Timestamp {
+            centiseconds: self.centiseconds + duration_centiseconds(duration),
+        }
+    }
+}
+
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    /// Subtract a duration, stopping at `Timestamp::ZERO` instead of going negative.
+    fn sub(self, duration: Duration) -> Timestamp {
+        Timestamp {
+            centiseconds: self.centiseconds.saturating_sub(duration_centiseconds(duration)),
+        }
+    }
+}
+
+impl Sub for Timestamp {
+    type Output = Duration;
+
+    /// The duration between two timestamps, zero if `other` is later than `self`.
+    fn sub(self, other: Timestamp) -> Duration {
+        Duration::from_millis(self.as_millis().saturating_sub(other.as_millis()))
+    }
+}
+
+impl AddAssign<Duration> for Timestamp {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl SubAssign<Duration> for Timestamp {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+fn invalid(text: &str) -> InvalidTimestamp {
+    InvalidTimestamp {
+        text: text.to_string(),
+    }
+}
+
+fn duration_centiseconds(duration: Duration) -> u64 {
+    ((duration.as_millis() + 5) / 10) as u64
+}
+
+/// Parse a number made of at most `max_len` digits.
+fn parse_number(text: &str, max_len: usize) -> Option<u64> {
+    if text.is_empty() || text.len() > max_len || !text.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// Parse the `H:MM:SS` part and the decimals of the seconds of a timestamp into milliseconds.
+fn parse_millis(clock: &str, fraction: &str) -> Option<u64> {
+    let parts: Vec<&str> = clock.split(':').collect();
+    let [hours, minutes, seconds] = parts[..] else {
+        return None;
+    };
+    let hours = parse_number(hours, 2)?;
+    let minutes = parse_number(minutes, 2).filter(|minutes| *minutes < 60)?;
+    let seconds = parse_number(seconds, 2).filter(|seconds| *seconds < 60)?;
+    let millis = parse_number(fraction, 3)? * 10u64.pow(3 - fraction.len() as u32);
+
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis)
+}
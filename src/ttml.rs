@@ -0,0 +1,1000 @@
+use crate::parser::push_override;
+use crate::tags::push_plain;
+use crate::{Alignment, AssColor, BorderStyle, Components, Dialogue, EventKind, ParseError, Segment, ConvertOptions, Tag, TaggedText, Timestamp, V4Format};
+use std::collections::HashMap;
+
+const TTML_NAMESPACES: &str = "xmlns=\"http://www.w3.org/ns/ttml\" \
+    xmlns:ttp=\"http://www.w3.org/ns/ttml#parameter\" \
+    xmlns:tts=\"http://www.w3.org/ns/ttml#styling\"";
+/// The text profile of IMSC1, the subset of TTML used by broadcasters.
+const IMSC1_TEXT_PROFILE: &str = "http://www.w3.org/ns/ttml/profile/imsc1/text";
+
+/// The `<style>` elements of a document, by their `xml:id`.
+type StyleMap<'a> = HashMap<&'a str, &'a Element>;
+
+/// An element of an XML document. The namespace prefixes of its name and of the names of its
+/// attributes are dropped, `tts:color` is read as `color` and `xml:id` as `id`.
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Node>,
+    /// The line the element starts on.
+    line: usize,
+}
+
+#[derive(Debug)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// The descendants named `name`, in the order of the document.
+    fn descendants<'a>(&'a self, name: &str, found: &mut Vec<&'a Element>) {
+        for element in self.elements() {
+            if element.name == name {
+                found.push(element);
+            }
+            element.descendants(name, found);
+        }
+    }
+}
+
+/// Reads the elements, attributes and text of an XML document, which is all TTML needs.
+/// Comments, processing instructions and the doctype are skipped.
+struct XmlReader<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+/// Parse an XML document into its root element.
+fn parse_xml(text: &str) -> Result<Element, ParseError> {
+    let mut reader = XmlReader { text, position: 0 };
+    loop {
+        reader.skip_whitespace();
+        let rest = reader.rest();
+        if rest.starts_with("<!--") {
+            reader.skip_past("-->")?;
+        } else if rest.starts_with("<?") {
+            reader.skip_past("?>")?;
+        } else if rest.starts_with("<!") {
+            reader.skip_past(">")?;
+        } else {
+            return reader.element();
+        }
+    }
+}
+
+impl<'a> XmlReader<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn line(&self) -> usize {
+        self.text[..self.position].matches('\n').count() + 1
+    }
+
+    /// A `ParseError::InvalidLine` for the line being read.
+    fn error(&self) -> ParseError {
+        ParseError::InvalidLine {
+            line: self.line(),
+            text: self.rest().lines().next().unwrap_or_default().to_string(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.position = self.text.len() - self.rest().trim_start().len();
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<(), ParseError> {
+        let i = self.rest().find(end).ok_or_else(|| self.error())?;
+        self.position += i + end.len();
+        Ok(())
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if !self.rest().starts_with(c) {
+            return Err(self.error());
+        }
+        self.position += c.len_utf8();
+        Ok(())
+    }
+
+    fn name(&mut self) -> Result<&'a str, ParseError> {
+        let rest = self.rest();
+        let end = rest.find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.error());
+        }
+        self.position += end;
+        Ok(&rest[..end])
+    }
+
+    /// Read an element, from its `<` to the end of its closing tag.
+    fn element(&mut self) -> Result<Element, ParseError> {
+        let line = self.line();
+        self.expect('<')?;
+        let mut element = Element {
+            name: local_name(self.name()?),
+            line,
+            ..Element::default()
+        };
+
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.position += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.position += 1;
+                break;
+            }
+            let name = local_name(self.name()?);
+            self.skip_whitespace();
+            self.expect('=')?;
+            self.skip_whitespace();
+            let quote = self.rest().chars().next().filter(|c| matches!(c, '"' | '\'')).ok_or_else(|| self.error())?;
+            self.position += 1;
+            let end = self.rest().find(quote).ok_or_else(|| self.error())?;
+            element.attributes.push((name, unescape(&self.rest()[..end])));
+            self.position += end + 1;
+        }
+
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                self.position += 2;
+                if local_name(self.name()?) != element.name {
+                    return Err(self.error());
+                }
+                self.skip_whitespace();
+                self.expect('>')?;
+                return Ok(element);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                let end = cdata.find("]]>").ok_or_else(|| self.error())?;
+                element.children.push(Node::Text(cdata[..end].to_string()));
+                self.position += "<![CDATA[".len() + end + "]]>".len();
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with('<') {
+                let child = self.element()?;
+                element.children.push(Node::Element(child));
+            } else if rest.is_empty() {
+                return Err(self.error());
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                element.children.push(Node::Text(unescape(&rest[..end])));
+                self.position += end;
+            }
+        }
+    }
+}
+
+/// `tts:color` without its namespace prefix.
+fn local_name(name: &str) -> String {
+    name.rsplit(':').next().unwrap_or(name).to_string()
+}
+
+/// Replace the entities and character references of XML text.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity.strip_prefix('#')
+                .and_then(|number| match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => number.parse().ok(),
+                })
+                .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[end + 1..];
+            },
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The value of a styling attribute of an element, or of the styles it refers to with its
+/// `style` attribute.
+fn style_attribute<'a>(element: &'a Element, name: &str, styles: &StyleMap<'a>) -> Option<&'a str> {
+    referred_attribute(element, name, styles, 0)
+}
+
+fn referred_attribute<'a>(element: &'a Element, name: &str, styles: &StyleMap<'a>, depth: usize) -> Option<&'a str> {
+    // styles referring to each other in a loop.
+    if depth > styles.len() {
+        return None;
+    }
+    element.attribute(name).or_else(|| {
+        element.attribute("style")?
+            .split_whitespace()
+            .filter_map(|id| styles.get(id))
+            .find_map(|style| referred_attribute(style, name, styles, depth + 1))
+    })
+}
+
+/// A `tts:color` value: `#RRGGBB`, `#RRGGBBAA`, `rgb(r,g,b)`, `rgba(r,g,b,a)` or a named colour.
+/// The alpha of TTML is an opacity, it is turned into the transparency of `AssColor`.
+fn parse_colour(value: &str) -> Option<AssColor> {
+    let value = value.trim();
+    let [red, green, blue, opacity] = if let Some(hex) = value.strip_prefix('#') {
+        let rgba = u32::from_str_radix(hex, 16).ok()?;
+        match hex.len() {
+            6 => {
+                let [_, red, green, blue] = rgba.to_be_bytes();
+                [red, green, blue, 255]
+            },
+            8 => rgba.to_be_bytes(),
+            _ => return None,
+        }
+    } else if let Some(arguments) = value.strip_prefix("rgba(").or_else(|| value.strip_prefix("rgb(")) {
+        let numbers = arguments.strip_suffix(')')?
+            .split(',')
+            .map(|number| number.trim().parse().ok())
+            .collect::<Option<Vec<u8>>>()?;
+        match numbers[..] {
+            [red, green, blue] => [red, green, blue, 255],
+            [red, green, blue, opacity] => [red, green, blue, opacity],
+            _ => return None,
+        }
+    } else {
+        match value.to_ascii_lowercase().as_str() {
+            "transparent" => [0, 0, 0, 0],
+            "black" => [0, 0, 0, 255],
+            "silver" => [192, 192, 192, 255],
+            "gray" | "grey" => [128, 128, 128, 255],
+            "white" => [255, 255, 255, 255],
+            "maroon" => [128, 0, 0, 255],
+            "red" => [255, 0, 0, 255],
+            "purple" => [128, 0, 128, 255],
+            "fuchsia" | "magenta" => [255, 0, 255, 255],
+            "green" => [0, 128, 0, 255],
+            "lime" => [0, 255, 0, 255],
+            "olive" => [128, 128, 0, 255],
+            "yellow" => [255, 255, 0, 255],
+            "navy" => [0, 0, 128, 255],
+            "blue" => [0, 0, 255, 255],
+            "teal" => [0, 128, 128, 255],
+            "aqua" | "cyan" => [0, 255, 255, 255],
+            _ => return None,
+        }
+    };
+    Some(AssColor::rgba(red, green, blue, 255 - opacity))
+}
+
+/// The `#RRGGBBAA` form of a colour, with its transparency turned into an opacity.
+fn colour_value(colour: AssColor) -> String {
+    format!("#{:02X}{:02X}{:02X}{:02X}", colour.red, colour.green, colour.blue, 255 - colour.alpha)
+}
+
+/// `12.5%`, without trailing zeros.
+fn percent(value: f64) -> String {
+    format!("{}%", (value * 100.0).round() / 100.0)
+}
+
+/// An `xml:id` for a style name, which may contain spaces.
+fn xml_id(name: &str) -> String {
+    let id: String = name.chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') { c } else { '_' })
+        .collect();
+    match id.chars().next() {
+        Some(c) if c.is_alphabetic() || c == '_' => id,
+        _ => format!("_{}", id),
+    }
+}
+
+/// The formatting TTML shares with override tags. `None` is the formatting of the style.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Formatting {
+    bold: Option<bool>,
+    italic: Option<bool>,
+    underline: Option<bool>,
+    strikeout: Option<bool>,
+    colour: Option<AssColor>,
+}
+
+impl Formatting {
+    fn from_style(style: &V4Format) -> Formatting {
+        Formatting {
+            bold: Some(style.get_bold().unwrap_or(false)),
+            italic: Some(style.get_italic().unwrap_or(false)),
+            underline: Some(style.get_underline().unwrap_or(false)),
+            strikeout: Some(style.get_strikeout().unwrap_or(false)),
+            colour: style.get_primarycolour(),
+        }
+    }
+
+    /// The formatting given by the attributes of an element and its styles.
+    fn read<'a>(element: &'a Element, styles: &StyleMap<'a>) -> Formatting {
+        let attribute = |name| style_attribute(element, name, styles);
+        let decorations: Vec<&str> = attribute("textDecoration").unwrap_or_default().split_whitespace().collect();
+        let decoration = |on: &str, off: &str| {
+            if decorations.contains(&on) {
+                Some(true)
+            } else if decorations.contains(&off) || decorations.contains(&"none") {
+                Some(false)
+            } else {
+                None
+            }
+        };
+
+        Formatting {
+            bold: attribute("fontWeight").map(|weight| weight.trim() == "bold"),
+            italic: attribute("fontStyle").map(|style| matches!(style.trim(), "italic" | "oblique")),
+            underline: decoration("underline", "noUnderline"),
+            strikeout: decoration("lineThrough", "noLineThrough"),
+            colour: attribute("color").and_then(parse_colour),
+        }
+    }
+
+    /// This formatting, changed where `inner` is set.
+    fn merge(self, inner: Formatting) -> Formatting {
+        Formatting {
+            bold: inner.bold.or(self.bold),
+            italic: inner.italic.or(self.italic),
+            underline: inner.underline.or(self.underline),
+            strikeout: inner.strikeout.or(self.strikeout),
+            colour: inner.colour.or(self.colour),
+        }
+    }
+
+    /// The override tags going from this formatting to `to`. A colour going back to the one of
+    /// the style, `base`, is written as `\c` without a value.
+    fn changes(&self, to: &Formatting, base: &Formatting) -> Vec<Tag> {
+        let changed = |from: Option<bool>, to: Option<bool>| to.filter(|_| to != from);
+        let mut tags = Vec::new();
+        if let Some(bold) = changed(self.bold, to.bold) {
+            tags.push(Tag::Bold(u32::from(bold)));
+        }
+        if let Some(italic) = changed(self.italic, to.italic) {
+            tags.push(Tag::Italic(italic));
+        }
+        if let Some(underline) = changed(self.underline, to.underline) {
+            tags.push(Tag::Underline(underline));
+        }
+        if let Some(strikeout) = changed(self.strikeout, to.strikeout) {
+            tags.push(Tag::StrikeOut(strikeout));
+        }
+        if to.colour != self.colour {
+            tags.push(match to.colour {
+                Some(colour) if to.colour != base.colour => Tag::Colour(1, colour),
+                _ => Tag::Unknown("c".to_string()),
+            });
+        }
+        tags
+    }
+
+    /// Follow the override tags of a block. `\c` does not change the transparency of the colour
+    /// of the style, `base`.
+    fn apply(&mut self, tags: &[Tag], base: &Formatting) {
+        for tag in tags {
+            match tag {
+                Tag::Bold(weight) => self.bold = Some(*weight != 0),
+                Tag::Italic(italic) => self.italic = Some(*italic),
+                Tag::Underline(underline) => self.underline = Some(*underline),
+                Tag::StrikeOut(strikeout) => self.strikeout = Some(*strikeout),
+                Tag::Colour(1, colour) => self.colour = Some(AssColor {
+                    alpha: base.colour.map_or(0, |colour| colour.alpha),
+                    ..*colour
+                }),
+                Tag::Unknown(tag) if tag == "c" || tag == "1c" => self.colour = None,
+                Tag::Reset(_) => *self = Formatting::default(),
+                _ => {},
+            }
+        }
+    }
+
+    /// The `tts:` attributes of the formatting which differs from the style, `base`.
+    fn attributes(&self, base: &Formatting) -> String {
+        let changed = |value: Option<bool>, base: Option<bool>| value.filter(|value| Some(*value) != base);
+        let mut attributes = String::new();
+        if let Some(bold) = changed(self.bold, base.bold) {
+            attributes.push_str(&format!(" tts:fontWeight=\"{}\"", if bold { "bold" } else { "normal" }));
+        }
+        if let Some(italic) = changed(self.italic, base.italic) {
+            attributes.push_str(&format!(" tts:fontStyle=\"{}\"", if italic { "italic" } else { "normal" }));
+        }
+        let decorations: Vec<&str> = [
+            changed(self.underline, base.underline).map(|on| if on { "underline" } else { "noUnderline" }),
+            changed(self.strikeout, base.strikeout).map(|on| if on { "lineThrough" } else { "noLineThrough" }),
+        ].into_iter().flatten().collect();
+        if !decorations.is_empty() {
+            attributes.push_str(&format!(" tts:textDecoration=\"{}\"", decorations.join(" ")));
+        }
+        if let Some(colour) = self.colour.filter(|colour| Some(*colour) != base.colour) {
+            attributes.push_str(&format!(" tts:color=\"{}\"", colour_value(colour)));
+        }
+        attributes
+    }
+}
+
+/// The parameters of a TTML document needed to read its times and lengths, and the PlayRes they
+/// are converted to.
+struct Document {
+    frame_rate: f64,
+    tick_rate: f64,
+    /// The size of the root container in pixels, the `tts:extent` of the `tt` element.
+    extent: Option<(f64, f64)>,
+    /// The columns and rows of `ttp:cellResolution`.
+    cells: (f64, f64),
+    width: f64,
+    height: f64,
+}
+
+impl Document {
+    fn new(root: &Element, options: &ConvertOptions) -> Document {
+        let numbers = |name: &str| -> Vec<f64> {
+            root.attribute(name).unwrap_or_default()
+                .split_whitespace()
+                .filter_map(|number| number.trim_end_matches("px").parse().ok())
+                .filter(|number: &f64| number.is_finite() && *number > 0.0)
+                .collect()
+        };
+        let multiplier = match numbers("frameRateMultiplier")[..] {
+            [numerator, denominator] => numerator / denominator,
+            _ => 1.0,
+        };
+        let frame_rate = numbers("frameRate").first().map(|rate| rate * multiplier);
+        let tick_rate = numbers("tickRate").first().copied().or(frame_rate).unwrap_or(1.0);
+        let extent = match numbers("extent")[..] {
+            [width, height] => Some((width, height)),
+            _ => None,
+        };
+        let cells = match numbers("cellResolution")[..] {
+            [columns, rows] => (columns, rows),
+            _ => (32.0, 15.0),
+        };
+
+        Document {
+            frame_rate: frame_rate.unwrap_or(30.0),
+            tick_rate,
+            extent,
+            cells,
+            width: f64::from(options.playresx),
+            height: f64::from(options.playresy),
+        }
+    }
+
+    /// A time in milliseconds: `HH:MM:SS.fff`, `HH:MM:SS:FF` in frames, or an offset like `1.5s`,
+    /// `500ms` or `90f`.
+    fn time(&self, value: &str) -> Option<f64> {
+        let value = value.trim();
+        let seconds = if value.contains(':') {
+            let parts: Vec<&str> = value.split(':').collect();
+            let (hours, minutes, seconds, frames) = match parts[..] {
+                [hours, minutes, seconds] => (hours, minutes, seconds, None),
+                [hours, minutes, seconds, frames] => (hours, minutes, seconds, Some(frames)),
+                _ => return None,
+            };
+            let frames = match frames {
+                Some(frames) => frames.parse::<f64>().ok()? / self.frame_rate,
+                None => 0.0,
+            };
+            hours.parse::<f64>().ok()? * 3600.0 + minutes.parse::<f64>().ok()? * 60.0 + seconds.parse::<f64>().ok()? + frames
+        } else {
+            let (number, metric) = value.split_at(value.find(|c: char| c.is_ascii_alphabetic())?);
+            let number: f64 = number.parse().ok()?;
+            match metric {
+                "h" => number * 3600.0,
+                "m" => number * 60.0,
+                "s" => number,
+                "ms" => number / 1000.0,
+                "f" => number / self.frame_rate,
+                "t" => number / self.tick_rate,
+                _ => return None,
+            }
+        };
+        Some(seconds * 1000.0).filter(|millis| millis.is_finite() && *millis >= 0.0)
+    }
+
+    /// The time attribute `name` of an element, `ParseError::BadTimestamp` if it is not valid.
+    fn time_attribute(&self, element: &Element, name: &str) -> Result<Option<f64>, ParseError> {
+        element.attribute(name)
+            .map(|value| self.time(value).ok_or_else(|| ParseError::BadTimestamp {
+                line: element.line,
+                text: value.to_string(),
+            }))
+            .transpose()
+    }
+
+    /// A length as a percentage of the width, or of the height when `vertical`, of the root
+    /// container.
+    fn percent(&self, length: &str, vertical: bool) -> Option<f64> {
+        let length = length.trim();
+        let (size, cells) = match (vertical, self.extent) {
+            (false, extent) => (extent.map_or(self.width, |extent| extent.0), self.cells.0),
+            (true, extent) => (extent.map_or(self.height, |extent| extent.1), self.cells.1),
+        };
+        if let Some(percent) = length.strip_suffix('%') {
+            percent.parse().ok()
+        } else if let Some(pixels) = length.strip_suffix("px") {
+            Some(pixels.parse::<f64>().ok()? / size * 100.0)
+        } else {
+            Some(length.strip_suffix('c')?.parse::<f64>().ok()? / cells * 100.0)
+        }
+    }
+
+    /// Two lengths like `10% 80%`, as percentages of the width and height of the root container.
+    fn pair(&self, value: &str) -> Option<(f64, f64)> {
+        let mut lengths = value.split_whitespace();
+        Some((self.percent(lengths.next()?, false)?, self.percent(lengths.next()?, true)?))
+    }
+
+    /// A vertical length in pixels of the PlayRes, like a font size. Percentages are relative to
+    /// `font_size`.
+    fn pixels(&self, length: &str, font_size: f64) -> Option<f64> {
+        // a font size may have a width and a height, the height is the size.
+        let length = length.split_whitespace().last()?;
+        match length.strip_suffix('%') {
+            Some(percent) => Some(percent.parse::<f64>().ok()? * font_size / 100.0),
+            None => Some(self.percent(length, true)? * self.height / 100.0),
+        }
+    }
+
+    /// The `V4Format` of a `<style>` element: `base` changed by its attributes.
+    fn ass_style<'a>(&self, element: &'a Element, styles: &StyleMap<'a>, base: &V4Format) -> V4Format {
+        let attribute = |name| style_attribute(element, name, styles);
+        let mut style = base.clone();
+        style.set_name(element.attribute("id").unwrap_or_default());
+
+        if let Some(family) = attribute("fontFamily").and_then(|families| families.split(',').next()) {
+            style.set_fontname(family.trim().trim_matches(['"', '\'']));
+        }
+        let base_size = base.get_fontsize().unwrap_or(20.0);
+        if let Some(size) = attribute("fontSize").and_then(|size| self.pixels(size, base_size)) {
+            style.set_fontsize((size * 100.0).round() / 100.0);
+        }
+
+        let formatting = Formatting::from_style(base).merge(Formatting::read(element, styles));
+        style.set_bold(formatting.bold == Some(true))
+            .set_italic(formatting.italic == Some(true))
+            .set_underline(formatting.underline == Some(true))
+            .set_strikeout(formatting.strikeout == Some(true));
+        if let Some(colour) = formatting.colour {
+            style.set_primarycolour(colour);
+        }
+
+        if let Some(colour) = attribute("backgroundColor").and_then(parse_colour).filter(|colour| colour.alpha < 255) {
+            style.set_borderstyle(BorderStyle::OpaqueBox).set_backcolour(colour);
+        }
+        if let Some(outline) = attribute("textOutline") {
+            if outline.trim() == "none" {
+                style.set_outline(0.0);
+            }
+            for part in outline.split_whitespace() {
+                match parse_colour(part) {
+                    Some(colour) => {
+                        style.set_outlinecolour(colour);
+                    },
+                    None => if let Some(width) = self.pixels(part, style.get_fontsize().unwrap_or(base_size)) {
+                        style.set_outline((width * 100.0).round() / 100.0);
+                    },
+                }
+            }
+        }
+        let alignment = style.get_alignment().unwrap_or_default();
+        if let Some(column) = attribute("textAlign").and_then(text_align_column) {
+            style.set_alignment(with_column(alignment, column));
+        }
+        style
+    }
+}
+
+/// The column, `1` to `3`, of a `tts:textAlign` value.
+fn text_align_column(value: &str) -> Option<u8> {
+    match value.trim() {
+        "left" | "start" => Some(1),
+        "center" => Some(2),
+        "right" | "end" => Some(3),
+        _ => None,
+    }
+}
+
+/// The `tts:textAlign` value of the column of an alignment.
+fn text_align(alignment: Alignment) -> &'static str {
+    match (alignment.numpad() - 1) % 3 {
+        0 => "left",
+        1 => "center",
+        _ => "right",
+    }
+}
+
+fn with_column(alignment: Alignment, column: u8) -> Alignment {
+    let row = (alignment.numpad() - 1) / 3 * 3;
+    Alignment::from_numpad(row + column).unwrap_or(alignment)
+}
+
+/// A `<p>` element with its times in milliseconds, and the style and region it inherits from
+/// its ancestors.
+struct Paragraph<'a> {
+    element: &'a Element,
+    begin: f64,
+    end: f64,
+    style: Option<&'a str>,
+    region: Option<&'a str>,
+}
+
+/// Find the paragraphs below `parent`, whose children begin at `begin` and end at `end`.
+fn collect_paragraphs<'a>(
+    document: &Document,
+    parent: &'a Element,
+    begin: f64,
+    end: Option<f64>,
+    inherited: (Option<&'a str>, Option<&'a str>),
+    paragraphs: &mut Vec<Paragraph<'a>>,
+) -> Result<(), ParseError> {
+    for element in parent.elements().filter(|element| matches!(element.name.as_str(), "body" | "div" | "p")) {
+        let element_begin = begin + document.time_attribute(element, "begin")?.unwrap_or(0.0);
+        let element_end = match (document.time_attribute(element, "end")?, document.time_attribute(element, "dur")?) {
+            (Some(element_end), _) => Some(begin + element_end),
+            (None, Some(duration)) => Some(element_begin + duration),
+            (None, None) => end,
+        };
+        let style = element.attribute("style").and_then(|style| style.split_whitespace().next()).or(inherited.0);
+        let region = element.attribute("region").or(inherited.1);
+
+        if element.name == "p" {
+            paragraphs.push(Paragraph {
+                element,
+                begin: element_begin,
+                end: element_end.unwrap_or(element_begin),
+                style,
+                region,
+            });
+        } else {
+            collect_paragraphs(document, element, element_begin, element_end, (style, region), paragraphs)?;
+        }
+    }
+    Ok(())
+}
+
+/// Push the text of an element and its `<span>` and `<br>` children. The formatting of a span is
+/// turned into override tags, and put back to `outer` after it.
+fn push_content<'a>(ass: &mut String, element: &'a Element, outer: Formatting, base: &Formatting, styles: &StyleMap<'a>) {
+    for node in &element.children {
+        match node {
+            Node::Text(text) => {
+                // whitespace in TTML is collapsed, like in HTML.
+                for c in text.chars() {
+                    if !c.is_whitespace() {
+                        ass.push(c);
+                    } else if !(ass.is_empty() || ass.ends_with(' ') || ass.ends_with("\\N")) {
+                        ass.push(' ');
+                    }
+                }
+            },
+            Node::Element(child) if child.name == "br" => {
+                ass.truncate(ass.trim_end_matches(' ').len());
+                ass.push_str("\\N");
+            },
+            Node::Element(child) if child.name == "span" => {
+                let inner = outer.merge(Formatting::read(child, styles));
+                for tag in outer.changes(&inner, base) {
+                    push_override(ass, &tag.to_string());
+                }
+                push_content(ass, child, inner, base, styles);
+                for tag in inner.changes(&outer, base) {
+                    push_override(ass, &tag.to_string());
+                }
+            },
+            Node::Element(child) => push_content(ass, child, outer, base, styles),
+        }
+    }
+}
+
+/// Read a TTML document into the styles of its `<style>` elements, based on the style of
+/// `options`, and a `Dialogue` for each `<p>` element.
+pub(crate) fn parse_ttml(contents: &str, options: &ConvertOptions) -> Result<(Vec<V4Format>, Vec<Dialogue>), ParseError> {
+    let root = parse_xml(contents.trim_start_matches('\u{feff}'))?;
+    if root.name != "tt" {
+        return Err(ParseError::UnsupportedFormat(format!("<{}>", root.name)));
+    }
+    let document = Document::new(&root, options);
+    let head = root.elements().find(|element| element.name == "head");
+
+    let mut style_elements = Vec::new();
+    let mut region_elements = Vec::new();
+    if let Some(head) = head {
+        head.descendants("style", &mut style_elements);
+        head.descendants("region", &mut region_elements);
+    }
+    let styles: StyleMap = style_elements.iter()
+        .filter_map(|style| Some((style.attribute("id")?, *style)))
+        .collect();
+    let regions: HashMap<&str, &Element> = region_elements.iter()
+        .filter_map(|region| Some((region.attribute("id")?, *region)))
+        .collect();
+
+    let ass_styles: Vec<V4Format> = style_elements.iter()
+        .filter(|style| style.attribute("id").is_some())
+        .map(|style| document.ass_style(style, &styles, &options.style))
+        .collect();
+
+    let mut paragraphs = Vec::new();
+    collect_paragraphs(&document, &root, 0.0, None, (None, None), &mut paragraphs)?;
+
+    let mut dialogues = Vec::new();
+    for paragraph in paragraphs {
+        let p = paragraph.element;
+        let style = paragraph.style
+            .and_then(|id| ass_styles.iter().find(|style| style.get_name().as_deref() == Some(id)))
+            .unwrap_or(&options.style);
+        let base = Formatting::from_style(style);
+
+        let mut ass = String::new();
+        let formatting = base.merge(Formatting::read(p, &styles));
+        for tag in base.changes(&formatting, &base) {
+            push_override(&mut ass, &tag.to_string());
+        }
+        push_content(&mut ass, p, formatting, &base, &styles);
+        ass.truncate(ass.trim_end_matches(' ').len());
+
+        let region = paragraph.region.and_then(|id| regions.get(id));
+        let style_alignment = style.get_alignment().unwrap_or_default();
+        let row = match region {
+            Some(region) => match style_attribute(region, "displayAlign", &styles).map(str::trim) {
+                Some("center") => 3,
+                Some("after") => 0,
+                _ => 6,
+            },
+            None => (style_alignment.numpad() - 1) / 3 * 3,
+        };
+        let column = style_attribute(p, "textAlign", &styles)
+            .or_else(|| style_attribute(region?, "textAlign", &styles))
+            .and_then(text_align_column)
+            .unwrap_or((style_alignment.numpad() - 1) % 3 + 1);
+        let alignment = Alignment::from_numpad(row + column).unwrap_or_default();
+
+        let margins = region.map_or([0; 3], |region| {
+            let origin = style_attribute(region, "origin", &styles).and_then(|origin| document.pair(origin)).unwrap_or((0.0, 0.0));
+            let extent = style_attribute(region, "extent", &styles).and_then(|extent| document.pair(extent))
+                .unwrap_or((100.0 - origin.0, 100.0 - origin.1));
+            let pixels = |percent: f64, size: f64| (percent.clamp(0.0, 100.0) * size / 100.0).round() as i32;
+            let vertical = match row {
+                6 => pixels(origin.1, document.height),
+                0 => pixels(100.0 - origin.1 - extent.1, document.height),
+                _ => 0,
+            };
+            [pixels(origin.0, document.width), pixels(100.0 - origin.0 - extent.0, document.width), vertical]
+        });
+        let style_margins = [style.get_marginl(), style.get_marginr(), style.get_marginv()];
+        let [left, right, vertical] = [0, 1, 2].map(|i| {
+            if Some(margins[i]) == style_margins[i] { 0 } else { margins[i] }
+        });
+
+        let mut text = TaggedText::parse(&ass);
+        if alignment != style_alignment {
+            match text.segments.first_mut() {
+                Some(Segment::Override(tags)) => tags.insert(0, Tag::Alignment(alignment)),
+                _ => text.segments.insert(0, Segment::Override(vec![Tag::Alignment(alignment)])),
+            }
+        }
+        dialogues.push(Dialogue::default()
+            .set_start(Timestamp::from_millis(paragraph.begin.round() as u64))
+            .set_end(Timestamp::from_millis(paragraph.end.round() as u64))
+            .set_style(&style.get_name().unwrap_or_default())
+            .set_marginl(&left.to_string())
+            .set_marginr(&right.to_string())
+            .set_marginv(&vertical.to_string())
+            .set_tagged_text(&text));
+    }
+    Ok((ass_styles, dialogues))
+}
+
+/// The attributes of a `<style>` element for a `V4Format`.
+fn style_attributes(style: &V4Format) -> String {
+    let mut attributes = format!(" xml:id=\"{}\"", escape(&xml_id(&style.get_name().unwrap_or_default())));
+    if let Some(font) = style.get_fontname() {
+        attributes.push_str(&format!(" tts:fontFamily=\"{}\"", escape(&font)));
+    }
+    if let Some(size) = style.get_fontsize() {
+        attributes.push_str(&format!(" tts:fontSize=\"{}px\"", size));
+    }
+    let base = Formatting {
+        bold: Some(false),
+        italic: Some(false),
+        underline: Some(false),
+        strikeout: Some(false),
+        colour: None,
+    };
+    attributes.push_str(&Formatting::from_style(style).attributes(&base));
+
+    attributes.push_str(&format!(" tts:textAlign=\"{}\"", text_align(style.get_alignment().unwrap_or_default())));
+
+    match style.get_borderstyle().unwrap_or_default() {
+        BorderStyle::OpaqueBox => if let Some(colour) = style.get_backcolour() {
+            attributes.push_str(&format!(" tts:backgroundColor=\"{}\"", colour_value(colour)));
+        },
+        BorderStyle::Outline => {
+            let outline = style.get_outline().unwrap_or(0.0);
+            if let Some(colour) = style.get_outlinecolour().filter(|_| outline > 0.0) {
+                attributes.push_str(&format!(" tts:textOutline=\"{} {}px\"", colour_value(colour), outline));
+            }
+        },
+    }
+    attributes
+}
+
+/// The content of a `<p>` element: the text of a `Dialogue`, with its formatting differing from
+/// the style in `<span>` elements and its lines separated by `<br/>`.
+fn paragraph_content(text: &TaggedText, base: &Formatting) -> String {
+    let drawings = text.drawing_segments();
+    let mut formatting = Formatting::default();
+    let mut content = String::new();
+
+    for (i, segment) in text.segments.iter().enumerate() {
+        match segment {
+            Segment::Override(tags) => formatting.apply(tags, base),
+            Segment::Text(_) if drawings.contains(&i) => {},
+            Segment::Text(text) => {
+                let mut plain = String::new();
+                push_plain(&mut plain, text);
+                let attributes = formatting.attributes(base);
+                for (n, line) in plain.split('\n').enumerate() {
+                    if n > 0 {
+                        content.push_str("<br/>");
+                    }
+                    if line.is_empty() {
+                        continue;
+                    }
+                    if attributes.is_empty() {
+                        content.push_str(&escape(line));
+                    } else {
+                        content.push_str(&format!("<span{}>{}</span>", attributes, escape(line)));
+                    }
+                }
+            },
+        }
+    }
+    content
+}
+
+/// Write the styles and dialogues of a file as a TTML document of the IMSC1 text profile.
+pub(crate) fn write_ttml(components: &Components) -> String {
+    let width = components.script.get_playresx().unwrap_or(384);
+    let height = components.script.get_playresy().unwrap_or(288);
+    let mut dialogues: Vec<&Dialogue> = components.events.dialogues.dialogues.iter()
+        .filter(|dialogue| matches!(dialogue.get_kind(), EventKind::Dialogue))
+        .collect();
+    dialogues.sort_by_key(|dialogue| dialogue.get_start());
+
+    // the regions are shared by the paragraphs with the same alignment and margins.
+    let mut regions: Vec<(Alignment, [i32; 3])> = Vec::new();
+    let mut paragraphs = String::new();
+    for dialogue in dialogues {
+        let (Some(text), Some(start), Some(end)) = (dialogue.get_tagged_text(), dialogue.get_start(), dialogue.get_end()) else {
+            continue;
+        };
+        let style = dialogue.get_style().and_then(|name| components.v4.get_style(&name));
+        let base = style.map(Formatting::from_style).unwrap_or_default();
+        let content = paragraph_content(&text, &base);
+        if content.replace("<br/>", "").trim().is_empty() {
+            continue;
+        }
+
+        let alignment = text.tags()
+            .find_map(|tag| match tag {
+                Tag::Alignment(alignment) => Some(*alignment),
+                _ => None,
+            })
+            .or_else(|| style?.get_alignment())
+            .unwrap_or_default();
+        let margin = |value: Option<String>, style_margin: Option<i32>| {
+            value.and_then(|value| value.trim().parse().ok())
+                .filter(|margin| *margin != 0)
+                .or(style_margin)
+                .unwrap_or(0)
+        };
+        let margins = [
+            margin(dialogue.get_marginl(), style.and_then(V4Format::get_marginl)),
+            margin(dialogue.get_marginr(), style.and_then(V4Format::get_marginr)),
+            margin(dialogue.get_marginv(), style.and_then(V4Format::get_marginv)),
+        ];
+        let region = match regions.iter().position(|region| *region == (alignment, margins)) {
+            Some(i) => i,
+            None => {
+                regions.push((alignment, margins));
+                regions.len() - 1
+            },
+        };
+
+        let mut attributes = style
+            .and_then(V4Format::get_name)
+            .map(|name| format!(" style=\"{}\"", escape(&xml_id(&name))))
+            .unwrap_or_default();
+        // the alignment of the style is overridden by the one of the paragraph.
+        if text_align(alignment) != text_align(style.and_then(V4Format::get_alignment).unwrap_or_default()) {
+            attributes.push_str(&format!(" tts:textAlign=\"{}\"", text_align(alignment)));
+        }
+        paragraphs.push_str(&format!(
+            "      <p begin=\"{}\" end=\"{}\" region=\"r{}\"{}>{}</p>\n",
+            start.to_vtt(),
+            end.to_vtt(),
+            region + 1,
+            attributes,
+            content,
+        ));
+    }
+
+    let mut ttml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    ttml.push_str(&format!(
+        "<tt {} ttp:profile=\"{}\" ttp:timeBase=\"media\" tts:extent=\"{}px {}px\" xml:lang=\"und\">\n",
+        TTML_NAMESPACES, IMSC1_TEXT_PROFILE, width, height,
+    ));
+    ttml.push_str("  <head>\n    <styling>\n");
+    for style in components.v4.iter() {
+        ttml.push_str(&format!("      <style{}/>\n", style_attributes(style)));
+    }
+    ttml.push_str("    </styling>\n    <layout>\n");
+    for (i, (alignment, [left, right, vertical])) in regions.iter().enumerate() {
+        let (width, height) = (f64::from(width), f64::from(height));
+        let left = f64::from(*left) / width * 100.0;
+        let right = f64::from(*right) / width * 100.0;
+        let vertical = f64::from(*vertical) / height * 100.0;
+        let (top, display_align) = match alignment.numpad() {
+            1..=3 => (vertical, "after"),
+            4..=6 => (0.0, "center"),
+            _ => (vertical, "before"),
+        };
+        let region_height = if display_align == "center" { 100.0 } else { 100.0 - 2.0 * vertical };
+        ttml.push_str(&format!(
+            "      <region xml:id=\"r{}\" tts:origin=\"{} {}\" tts:extent=\"{} {}\" tts:displayAlign=\"{}\" tts:textAlign=\"{}\"/>\n",
+            i + 1,
+            percent(left),
+            percent(top),
+            percent((100.0 - left - right).max(0.0)),
+            percent(region_height.max(0.0)),
+            display_align,
+            text_align(*alignment),
+        ));
+    }
+    ttml.push_str("    </layout>\n  </head>\n  <body>\n    <div>\n");
+    ttml.push_str(&paragraphs);
+    ttml.push_str("    </div>\n  </body>\n</tt>\n");
+    ttml
+}
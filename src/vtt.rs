@@ -0,0 +1,525 @@
+use crate::parser::{push_override, push_text};
+use crate::{Alignment, AssColor, ParseError, Tag, Timestamp};
+use std::fmt;
+
+const VTT_HEADER: &str = "WEBVTT";
+const VTT_ARROW: &str = "-->";
+
+/// The colour classes of WebVTT, used as `<c.yellow>`.
+const COLOURS: [(&str, [u8; 3]); 8] = [
+    ("white", [255, 255, 255]),
+    ("lime", [0, 255, 0]),
+    ("cyan", [0, 255, 255]),
+    ("red", [255, 0, 0]),
+    ("yellow", [255, 255, 0]),
+    ("magenta", [255, 0, 255]),
+    ("blue", [0, 0, 255]),
+    ("black", [0, 0, 0]),
+];
+
+/// The character references of cue texts, with the text they stand for in a `Dialogue`.
+const ENTITIES: [(&str, &str); 6] = [
+    ("&amp;", "&"),
+    ("&lt;", "<"),
+    ("&gt;", ">"),
+    ("&nbsp;", "\\h"),
+    ("&lrm;", "\u{200e}"),
+    ("&rlm;", "\u{200f}"),
+];
+
+/// A WebVTT (`.vtt`) file, as used by web video players.
+///
+/// Cues keep their text as it is written in the file, with the `<b>` or `<c.yellow>` tags of
+/// WebVTT. `VttCue::ass_text` turns them into override tags and `AssFile::convert_vtt` converts
+/// a whole file. Writing a `WebVtt` with `to_string` gives back a `.vtt` file.
+///
+/// ```rust
+/// use ass_parser::{Alignment, VttBlock, WebVtt};
+///
+/// let vtt = WebVtt::parse("WEBVTT\n\nNOTE a comment\n\n00:01.500 --> 00:04.900 line:0\n<i>Hello</i>\n").unwrap();
+/// assert_eq!(VttBlock::Note("a comment".to_string()), vtt.blocks[0]);
+///
+/// let cue = vtt.cues().next().unwrap();
+/// assert_eq!("<i>Hello</i>", cue.text);
+/// assert_eq!("{\\i1}Hello{\\i0}", cue.ass_text());
+/// assert_eq!(Alignment::TopCenter, cue.settings.alignment());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WebVtt {
+    /// The text after `WEBVTT` on the first line.
+    pub header: String,
+    pub blocks: Vec<VttBlock>,
+}
+
+/// A block of a WebVTT file, separated from the others by blank lines.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VttBlock {
+    /// A subtitle with its timing.
+    Cue(VttCue),
+    /// `NOTE`, a comment.
+    Note(String),
+    /// `STYLE`, a CSS style sheet for the cues.
+    Style(String),
+    /// `REGION`, the settings of an area of the video cues can be displayed in.
+    Region(String),
+}
+
+/// A subtitle of a WebVTT file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VttCue {
+    /// The identifier written before the timing line, empty when there is none.
+    pub identifier: String,
+    /// the start timestamp.
+    pub start: Timestamp,
+    /// the end timestamp.
+    pub end: Timestamp,
+    /// The settings written after the timing.
+    pub settings: CueSettings,
+    /// The text of the cue with its WebVTT tags. Lines are separated by `\n`.
+    pub text: String,
+}
+
+/// The settings written after the timing of a cue, eg. `line:10% align:start`.
+///
+/// The values are kept as they are written, `alignment` and `margins` give the placement of the
+/// cue in the terms of `.ass` files.
+///
+/// ```rust
+/// use ass_parser::{Alignment, CueSettings};
+///
+/// let settings = CueSettings::parse("line:10%,start position:25% align:start");
+/// assert_eq!(Alignment::TopLeft, settings.alignment());
+/// assert_eq!([96, 0, 29], settings.margins(384, 288));
+///
+/// let settings = CueSettings::from_alignment(Alignment::TopLeft, [96, 0, 29], 384, 288);
+/// assert_eq!("line:10.07%,start position:25% align:start", settings.to_string());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CueSettings {
+    /// `line:`, a line number, or a percentage of the height of the video optionally followed by
+    /// `,start`, `,center` or `,end`.
+    pub line: Option<String>,
+    /// `position:`, a percentage of the width of the video optionally followed by `,line-left`,
+    /// `,center` or `,line-right`.
+    pub position: Option<String>,
+    /// `size:`, the width of the cue as a percentage of the width of the video.
+    pub size: Option<String>,
+    /// `align:`, `start`, `center`, `end`, `left` or `right`.
+    pub align: Option<String>,
+    /// The other settings, like `vertical:` and `region:`.
+    pub other: Vec<(String, String)>,
+}
+
+impl WebVtt {
+    /// Parse the content of a `.vtt` file. A `ParseError::UnsupportedFormat` is returned when it
+    /// does not start with `WEBVTT`, and a `ParseError::BadTimestamp` for a timing line which is
+    /// not `[HH:]MM:SS.mmm --> [HH:]MM:SS.mmm`. Blocks which are neither a cue nor a `NOTE`,
+    /// `STYLE` or `REGION` block are skipped, and so are the header lines after the first one.
+    pub fn parse(contents: &str) -> Result<WebVtt, ParseError> {
+        let contents = contents.trim_start_matches('\u{feff}');
+        let mut blocks = get_blocks(contents).into_iter();
+
+        let first = blocks.next().map_or("", |(_, lines)| lines[0]);
+        let header = match first.strip_prefix(VTT_HEADER) {
+            Some(rest) if rest.is_empty() || rest.starts_with([' ', '\t']) => rest.trim(),
+            _ => return Err(ParseError::UnsupportedFormat(first.to_string())),
+        };
+
+        let mut vtt = WebVtt {
+            header: header.to_string(),
+            blocks: Vec::new(),
+        };
+        for (first_line, lines) in blocks {
+            if let Some(block) = parse_block(first_line, &lines)? {
+                vtt.blocks.push(block);
+            }
+        }
+        Ok(vtt)
+    }
+
+    /// Iterate over the cues of the file.
+    pub fn cues(&self) -> impl Iterator<Item = &VttCue> {
+        self.blocks.iter().filter_map(|block| match block {
+            VttBlock::Cue(cue) => Some(cue),
+            _ => None,
+        })
+    }
+}
+
+/// The blocks of lines separated by blank lines, with the line number of their first line.
+fn get_blocks(contents: &str) -> Vec<(usize, Vec<&str>)> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut first_line = 1;
+
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                blocks.push((first_line, current));
+                current = Vec::new();
+            }
+        } else {
+            if current.is_empty() {
+                first_line = i + 1;
+            }
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        blocks.push((first_line, current));
+    }
+    blocks
+}
+
+fn parse_block(first_line: usize, lines: &[&str]) -> Result<Option<VttBlock>, ParseError> {
+    if let Some(rest) = keyword(lines[0], "NOTE") {
+        let note: Vec<&str> = std::iter::once(rest)
+            .filter(|rest| !rest.is_empty())
+            .chain(lines[1..].iter().copied())
+            .collect();
+        return Ok(Some(VttBlock::Note(note.join("\n"))));
+    }
+    if keyword(lines[0], "STYLE") == Some("") {
+        return Ok(Some(VttBlock::Style(lines[1..].join("\n"))));
+    }
+    if keyword(lines[0], "REGION") == Some("") {
+        return Ok(Some(VttBlock::Region(lines[1..].join("\n"))));
+    }
+
+    let timing = match lines {
+        [timing, ..] if timing.contains(VTT_ARROW) => 0,
+        [_, timing, ..] if timing.contains(VTT_ARROW) => 1,
+        _ => return Ok(None),
+    };
+    let (start, end, settings) = parse_timing(first_line + timing, lines[timing])?;
+
+    Ok(Some(VttBlock::Cue(VttCue {
+        identifier: if timing == 1 { lines[0].trim().to_string() } else { String::new() },
+        start,
+        end,
+        settings,
+        text: lines[timing + 1..].join("\n"),
+    })))
+}
+
+/// The rest of `line` when it is `name` alone or followed by a space.
+fn keyword<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(name)?;
+    (rest.is_empty() || rest.starts_with([' ', '\t'])).then(|| rest.trim())
+}
+
+/// Parse `00:01.500 --> 00:04.900 line:0`.
+fn parse_timing(line: usize, text: &str) -> Result<(Timestamp, Timestamp, CueSettings), ParseError> {
+    let bad_timestamp = || ParseError::BadTimestamp {
+        line,
+        text: text.to_string(),
+    };
+    let (start, rest) = text.split_once(VTT_ARROW).ok_or_else(bad_timestamp)?;
+    let rest = rest.trim_start();
+    let (end, settings) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+    let start = Timestamp::from_vtt(start).map_err(|_| bad_timestamp())?;
+    let end = Timestamp::from_vtt(end).map_err(|_| bad_timestamp())?;
+    Ok((start, end, CueSettings::parse(settings)))
+}
+
+impl VttCue {
+    /// The text of the cue with `<b>`, `<i>`, `<u>` and the colour classes of `<c>` turned into
+    /// override tags, and the line breaks into `\N`. The other tags, like `<v>`, `<ruby>` or
+    /// timestamps, are dropped.
+    pub fn ass_text(&self) -> String {
+        vtt_to_ass(&self.text).0
+    }
+
+    /// The speaker of the first `<v Speaker>` tag of the cue.
+    pub fn voice(&self) -> Option<String> {
+        vtt_to_ass(&self.text).1
+    }
+}
+
+fn vtt_to_ass(text: &str) -> (String, Option<String>) {
+    let mut ass = String::new();
+    let mut voice = None;
+    // the colour set by each `<c>` tag which is still open, `None` when it has no colour class.
+    let mut classes: Vec<Option<AssColor>> = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(['<', '&']) {
+        push_text(&mut ass, &rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with('&') {
+            let (text, length) = ENTITIES.iter()
+                .find(|(entity, _)| rest.starts_with(entity))
+                .map_or(("&", 1), |(entity, text)| (*text, entity.len()));
+            ass.push_str(text);
+            rest = &rest[length..];
+        } else {
+            let Some(end) = rest.find('>') else {
+                break;
+            };
+            if let Some(tag) = span_tag(&rest[1..end], &mut classes, &mut voice) {
+                push_override(&mut ass, &tag.to_string());
+            }
+            rest = &rest[end + 1..];
+        }
+    }
+    push_text(&mut ass, rest);
+    (ass, voice)
+}
+
+/// The override tag for a tag of a cue like `<b>`, `</i>` or `<c.yellow>`.
+fn span_tag(tag: &str, classes: &mut Vec<Option<AssColor>>, voice: &mut Option<String>) -> Option<Tag> {
+    let (closing, tag) = match tag.strip_prefix('/') {
+        Some(tag) => (true, tag),
+        None => (false, tag),
+    };
+    let (name, annotation) = tag.split_once([' ', '\t']).unwrap_or((tag, ""));
+    let mut name = name.split('.');
+    let flag = if closing { 0 } else { 1 };
+
+    match (name.next()?, closing) {
+        ("b", _) => Some(Tag::Bold(flag)),
+        ("i", _) => Some(Tag::Italic(!closing)),
+        ("u", _) => Some(Tag::Underline(!closing)),
+        ("c", false) => {
+            let colour = name.find_map(colour_from_name);
+            classes.push(colour);
+            colour.map(|colour| Tag::Colour(1, colour))
+        },
+        ("c", true) => {
+            classes.pop().flatten()?;
+            match classes.iter().rev().flatten().next() {
+                Some(colour) => Some(Tag::Colour(1, *colour)),
+                // `\c` without a value goes back to the colour of the style.
+                None => Some(Tag::Unknown("c".to_string())),
+            }
+        },
+        ("v", false) => {
+            if voice.is_none() && !annotation.trim().is_empty() {
+                *voice = Some(annotation.trim().to_string());
+            }
+            None
+        },
+        _ => None,
+    }
+}
+
+fn colour_from_name(name: &str) -> Option<AssColor> {
+    COLOURS.iter()
+        .find(|(colour, _)| *colour == name)
+        .map(|(_, [red, green, blue])| AssColor::rgb(*red, *green, *blue))
+}
+
+/// The WebVTT colour class of `colour`, if there is one. Its alpha is ignored.
+pub(crate) fn colour_name(colour: AssColor) -> Option<&'static str> {
+    COLOURS.iter()
+        .find(|(_, rgb)| *rgb == [colour.red, colour.green, colour.blue])
+        .map(|(name, _)| *name)
+}
+
+/// Escape the characters which cannot be written as they are in the text of a cue.
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+impl CueSettings {
+    /// Parse settings like `line:10% align:start`. Settings which are not `name:value` are
+    /// ignored.
+    pub fn parse(text: &str) -> CueSettings {
+        let mut settings = CueSettings::default();
+        for (name, value) in text.split_whitespace().filter_map(|setting| setting.split_once(':')) {
+            let setting = match name {
+                "line" => &mut settings.line,
+                "position" => &mut settings.position,
+                "size" => &mut settings.size,
+                "align" => &mut settings.align,
+                _ => {
+                    settings.other.push((name.to_string(), value.to_string()));
+                    continue;
+                },
+            };
+            *setting = Some(value.to_string());
+        }
+        settings
+    }
+
+    /// The settings placing a cue like a `Dialogue` with `alignment` and the left, right and
+    /// vertical `margins`, on a screen of `width` x `height` pixels. A margin of `0` leaves the
+    /// placement to the player.
+    pub fn from_alignment(alignment: Alignment, margins: [i32; 3], width: u32, height: u32) -> CueSettings {
+        let [left, right, vertical] = margins;
+        let (left, right) = (to_percent(left, width), to_percent(right, width));
+        let vertical = to_percent(vertical, height);
+        let mut settings = CueSettings {
+            line: match alignment.numpad() {
+                7..=9 => Some(format!("{},start", format_percent(vertical))),
+                4..=6 => Some("50%,center".to_string()),
+                _ if vertical > 0.0 => Some(format!("{},end", format_percent(100.0 - vertical))),
+                _ => None,
+            },
+            ..CueSettings::default()
+        };
+        match alignment.numpad() % 3 {
+            1 => {
+                settings.position = (left > 0.0).then(|| format_percent(left));
+                settings.align = Some("start".to_string());
+            },
+            0 => {
+                settings.position = (right > 0.0).then(|| format_percent(100.0 - right));
+                settings.align = Some("end".to_string());
+            },
+            _ if left > 0.0 || right > 0.0 => {
+                let size = 100.0 - left - right;
+                settings.position = Some(format_percent(left + size / 2.0));
+                settings.size = Some(format_percent(size));
+            },
+            _ => {},
+        }
+        settings
+    }
+
+    /// The alignment of the cue, `align` gives its column and `line` its row.
+    pub fn alignment(&self) -> Alignment {
+        let column = match self.align.as_deref() {
+            Some("start" | "left") => 1,
+            Some("end" | "right") => 3,
+            _ => 2,
+        };
+        let row = match self.line.as_deref().map(split_value) {
+            Some((value, align)) => match (parse_percent(value), value.parse::<i32>()) {
+                (Some(_), _) if align == Some("center") => 3,
+                (Some(line), _) if line < 50.0 => 6,
+                (None, Ok(line)) if line >= 0 => 6,
+                _ => 0,
+            },
+            None => 0,
+        };
+        Alignment::from_numpad(row + column).unwrap_or_default()
+    }
+
+    /// The left, right and vertical margins of the cue on a screen of `width` x `height`
+    /// pixels, computed from the percentages of `position`, `size` and `line`. A margin is `0`
+    /// when the margin of the style should be used.
+    pub fn margins(&self, width: u32, height: u32) -> [i32; 3] {
+        let pixels = |percent: f64, size: u32| (percent.clamp(0.0, 100.0) * size as f64 / 100.0).round() as i32;
+        let alignment = self.alignment().numpad();
+
+        let line = self.line.as_deref().and_then(|line| parse_percent(split_value(line).0));
+        let vertical = match (line, alignment) {
+            (Some(line), 7..=9) => pixels(line, height),
+            (Some(line), 1..=3) => pixels(100.0 - line, height),
+            _ => 0,
+        };
+        let position = self.position.as_deref().and_then(|position| parse_percent(split_value(position).0));
+        let size = self.size.as_deref().and_then(parse_percent);
+        let [left, right] = match (alignment % 3, position, size) {
+            (1, Some(position), _) => [pixels(position, width), 0],
+            (0, Some(position), _) => [0, pixels(100.0 - position, width)],
+            (2, position, Some(size)) => {
+                let position = position.unwrap_or(50.0);
+                [pixels(position - size / 2.0, width), pixels(100.0 - position - size / 2.0, width)]
+            },
+            _ => [0, 0],
+        };
+        [left, right, vertical]
+    }
+}
+
+/// Split `10%,start` into the value and its alignment.
+fn split_value(text: &str) -> (&str, Option<&str>) {
+    match text.split_once(',') {
+        Some((value, align)) => (value, Some(align)),
+        None => (text, None),
+    }
+}
+
+fn parse_percent(text: &str) -> Option<f64> {
+    text.strip_suffix('%')?.parse().ok().filter(|percent: &f64| percent.is_finite())
+}
+
+fn to_percent(pixels: i32, size: u32) -> f64 {
+    pixels.max(0) as f64 * 100.0 / size.max(1) as f64
+}
+
+/// Percentages are rounded to two decimals.
+fn format_percent(percent: f64) -> String {
+    // adding 0.0 turns -0 into 0.
+    format!("{}%", (percent * 100.0).round() / 100.0 + 0.0)
+}
+
+impl fmt::Display for CueSettings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let settings = [
+            ("line", &self.line),
+            ("position", &self.position),
+            ("size", &self.size),
+            ("align", &self.align),
+        ];
+        let settings = settings.iter()
+            .filter_map(|(name, value)| Some((*name, value.as_deref()?)))
+            .chain(self.other.iter().map(|(name, value)| (name.as_str(), value.as_str())));
+
+        for (i, (name, value)) in settings.enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}:{}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for VttCue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.identifier.is_empty() {
+            writeln!(f, "{}", self.identifier)?;
+        }
+        write!(f, "{} {} {}", self.start.to_vtt(), VTT_ARROW, self.end.to_vtt())?;
+        let settings = self.settings.to_string();
+        if !settings.is_empty() {
+            write!(f, " {}", settings)?;
+        }
+        writeln!(f)?;
+        write_lines(f, &self.text)
+    }
+}
+
+impl fmt::Display for WebVtt {
+    /// Blocks are separated by a blank line, and the blank lines inside a block are left out.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", VTT_HEADER)?;
+        if !self.header.is_empty() {
+            write!(f, " {}", self.header)?;
+        }
+        writeln!(f)?;
+
+        for block in &self.blocks {
+            writeln!(f)?;
+            match block {
+                VttBlock::Cue(cue) => write!(f, "{}", cue)?,
+                VttBlock::Note(note) if !note.contains('\n') && !note.is_empty() => writeln!(f, "NOTE {}", note)?,
+                VttBlock::Note(note) => {
+                    writeln!(f, "NOTE")?;
+                    write_lines(f, note)?;
+                },
+                VttBlock::Style(css) => {
+                    writeln!(f, "STYLE")?;
+                    write_lines(f, css)?;
+                },
+                VttBlock::Region(settings) => {
+                    writeln!(f, "REGION")?;
+                    write_lines(f, settings)?;
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write the lines of `text` which are not blank, a blank line would end the block.
+fn write_lines(f: &mut fmt::Formatter, text: &str) -> fmt::Result {
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        writeln!(f, "{}", line)?;
+    }
+    Ok(())
+}